@@ -1,10 +1,13 @@
 use crate::{
-    boot::setup::AppData, error::MyError, handler::cluster::get_cluster,
-    resource::cluster::Cluster, utils,
+    boot::setup::AppData,
+    error::MyError,
+    handler::{cluster::get_cluster, port_forward_profile},
+    resource::cluster::Cluster,
+    utils,
 };
 use kube::{config::KubeConfigOptions, Config, Discovery};
 use std::sync::Mutex;
-use tauri::State;
+use tauri::{AppHandle, State};
 
 #[tauri::command]
 pub async fn list_clusters(state: State<'_, Mutex<AppData>>) -> Result<Vec<Cluster>, MyError> {
@@ -19,6 +22,7 @@ pub async fn list_clusters(state: State<'_, Mutex<AppData>>) -> Result<Vec<Clust
 #[tauri::command]
 pub async fn switch_cluster(
     cluster_name: String,
+    app: AppHandle,
     state: State<'_, Mutex<AppData>>,
 ) -> Result<String, MyError> {
     let kube_config = {
@@ -31,10 +35,20 @@ pub async fn switch_cluster(
     let client = utils::cluster::generate_client(&config)?;
     let discovery = Discovery::new(client.clone()).run().await?;
 
-    let mut app_data = state.lock().unwrap();
-    app_data.client = Some(client);
-    app_data.discovery = Some(discovery);
+    let port_forward = {
+        let mut app_data = state.lock().unwrap();
+        app_data.client = Some(client.clone());
+        app_data.discovery = Some(discovery);
+        // 缓存绑定旧集群的 client，切换后重新建立
+        app_data.graph_cache.clear();
+        app_data.port_forward.clone()
+    };
 
     tracing::info!("Switched to cluster {}", cluster_name);
+    // 自动启动在后台进行，不阻塞切换集群的返回
+    let context = cluster_name.clone();
+    tauri::async_runtime::spawn(async move {
+        port_forward_profile::start_on_switch(&app, &port_forward, client, &context).await;
+    });
     Ok(cluster_name)
 }
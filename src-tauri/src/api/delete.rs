@@ -0,0 +1,93 @@
+use crate::{
+    boot::setup::AppData,
+    error::MyError,
+    handler::delete,
+    resource::delete::{DeleteResult, DependentNode},
+    utils::discovery::{self, ResourceRef},
+};
+use kube::api::DeleteParams;
+use serde::Deserialize;
+use std::{sync::Mutex, time::Duration};
+use tauri::State;
+
+// 默认等待删除完成的时间
+const DEFAULT_DELETE_TIMEOUT: u64 = 60;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeleteOptions {
+    // Foreground/Background/Orphan，默认由服务端决定
+    propagation_policy: Option<String>,
+    grace_period_seconds: Option<u32>,
+    // 等待对象消失的秒数，为 0 时不等待
+    timeout: Option<u64>,
+}
+
+/// 删除前预览会被级联删除的依赖树
+#[tauri::command]
+pub async fn delete_preview(
+    resource: ResourceRef,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<DependentNode, MyError> {
+    let (client, (api_resource, capabilities), resources) = {
+        let app_data = state.lock().unwrap();
+        let discovery = app_data
+            .discovery
+            .as_ref()
+            .ok_or_else(|| MyError::NoClient("Discovery not available".to_string()))?;
+        (
+            app_data.client.clone().unwrap(),
+            resource.resolve(discovery)?,
+            discovery::listable_resources(discovery),
+        )
+    };
+    delete::dependents(
+        client,
+        &resources,
+        &api_resource,
+        &capabilities,
+        resource.namespace.as_deref(),
+        &resource.name,
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_resource(
+    resource: ResourceRef,
+    options: DeleteOptions,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<DeleteResult, MyError> {
+    let (client, (api_resource, capabilities)) = {
+        let app_data = state.lock().unwrap();
+        let discovery = app_data
+            .discovery
+            .as_ref()
+            .ok_or_else(|| MyError::NoClient("Discovery not available".to_string()))?;
+        (
+            app_data.client.clone().unwrap(),
+            resource.resolve(discovery)?,
+        )
+    };
+    let params = DeleteParams {
+        propagation_policy: options
+            .propagation_policy
+            .as_deref()
+            .map(delete::parse_propagation_policy)
+            .transpose()?,
+        grace_period_seconds: options.grace_period_seconds,
+        ..DeleteParams::default()
+    };
+    let timeout = Some(options.timeout.unwrap_or(DEFAULT_DELETE_TIMEOUT))
+        .filter(|timeout| *timeout > 0)
+        .map(Duration::from_secs);
+    delete::delete(
+        client,
+        &api_resource,
+        &capabilities,
+        resource.namespace.as_deref(),
+        &resource.name,
+        params,
+        timeout,
+    )
+    .await
+}
@@ -0,0 +1,76 @@
+use crate::{
+    boot::setup::AppData,
+    error::MyError,
+    handler::finalizer,
+    resource::finalizer::FinalizerDiagnostics,
+    utils::discovery::{self, ResourceRef},
+};
+use std::sync::Mutex;
+use tauri::State;
+
+#[tauri::command]
+pub async fn finalizer_diagnostics(
+    resource: ResourceRef,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<FinalizerDiagnostics, MyError> {
+    let (client, (api_resource, capabilities), resources) = {
+        let app_data = state.lock().unwrap();
+        let discovery = app_data
+            .discovery
+            .as_ref()
+            .ok_or_else(|| MyError::NoClient("Discovery not available".to_string()))?;
+        (
+            app_data.client.clone().unwrap(),
+            resource.resolve(discovery)?,
+            discovery::listable_resources(discovery),
+        )
+    };
+    finalizer::diagnose(
+        client,
+        &resources,
+        &api_resource,
+        &capabilities,
+        resource.namespace.as_deref(),
+        &resource.name,
+    )
+    .await
+}
+
+/// 强制移除 finalizer，confirm 必须与对象名一致
+#[tauri::command]
+pub async fn remove_finalizers(
+    resource: ResourceRef,
+    confirm: String,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<(), MyError> {
+    if confirm != resource.name {
+        return Err(MyError::OtherError(format!(
+            "Confirmation does not match {}",
+            resource.name
+        )));
+    }
+    let (client, (api_resource, capabilities)) = {
+        let app_data = state.lock().unwrap();
+        let discovery = app_data
+            .discovery
+            .as_ref()
+            .ok_or_else(|| MyError::NoClient("Discovery not available".to_string()))?;
+        (
+            app_data.client.clone().unwrap(),
+            resource.resolve(discovery)?,
+        )
+    };
+    tracing::warn!(
+        "Removing finalizers from {} {}",
+        api_resource.kind,
+        resource.name
+    );
+    finalizer::remove_finalizers(
+        client,
+        &api_resource,
+        &capabilities,
+        resource.namespace.as_deref(),
+        &resource.name,
+    )
+    .await
+}
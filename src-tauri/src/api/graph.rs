@@ -0,0 +1,34 @@
+use crate::{
+    boot::setup::AppData, error::MyError, handler::graph, resource::graph::ResourceGraph,
+    utils::discovery::ResourceRef,
+};
+use std::sync::Mutex;
+use tauri::State;
+
+#[tauri::command]
+pub async fn resource_graph(
+    resource: ResourceRef,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<ResourceGraph, MyError> {
+    let (client, cache, (api_resource, capabilities)) = {
+        let app_data = state.lock().unwrap();
+        let discovery = app_data
+            .discovery
+            .as_ref()
+            .ok_or_else(|| MyError::NoClient("Discovery not available".to_string()))?;
+        (
+            app_data.client.clone().unwrap(),
+            app_data.graph_cache.clone(),
+            resource.resolve(discovery)?,
+        )
+    };
+    graph::relationships(
+        client,
+        &cache,
+        &api_resource,
+        &capabilities,
+        resource.namespace.as_deref(),
+        &resource.name,
+    )
+    .await
+}
@@ -1,4 +1,13 @@
 pub mod cluster;
+pub mod delete;
+pub mod finalizer;
+pub mod graph;
 pub mod k8s_proxy;
+pub mod namespace;
+pub mod node;
 pub mod pods;
+pub mod port_forward;
+pub mod recording;
+pub mod rollout;
+pub mod scale;
 pub mod websocket;
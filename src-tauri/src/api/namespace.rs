@@ -0,0 +1,44 @@
+use crate::{
+    boot::setup::AppData,
+    error::MyError,
+    handler::namespace_sleep,
+    resource::namespace_sleep::{NamespaceSleepResult, NamespaceSleepState},
+};
+use std::sync::Mutex;
+use tauri::State;
+
+#[tauri::command]
+pub async fn sleep_namespace(
+    namespace: String,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<NamespaceSleepResult, MyError> {
+    let client = {
+        let app_data = state.lock().unwrap();
+        app_data.client.clone().unwrap()
+    };
+    namespace_sleep::sleep(client, &namespace).await
+}
+
+#[tauri::command]
+pub async fn wake_namespace(
+    namespace: String,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<NamespaceSleepResult, MyError> {
+    let client = {
+        let app_data = state.lock().unwrap();
+        app_data.client.clone().unwrap()
+    };
+    namespace_sleep::wake(client, &namespace).await
+}
+
+#[tauri::command]
+pub async fn namespace_sleep_state(
+    namespace: String,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<Option<NamespaceSleepState>, MyError> {
+    let client = {
+        let app_data = state.lock().unwrap();
+        app_data.client.clone().unwrap()
+    };
+    namespace_sleep::sleep_state(client, &namespace).await
+}
@@ -0,0 +1,145 @@
+use crate::{
+    boot::{setup::AppData, websocket::WebsocketManager},
+    error::MyError,
+    handler::{
+        node_shell::{self, DEFAULT_NODE_SHELL_IMAGE, DEFAULT_NODE_SHELL_NAMESPACE},
+        terminal::{self, TerminalOptions, DEFAULT_SHELLS},
+    },
+};
+use k8s_openapi::api::core::v1::Pod;
+use kube::{
+    api::{Api, AttachParams, PostParams},
+    runtime::{conditions::is_pod_running, wait::await_condition},
+};
+use serde::Deserialize;
+use std::{sync::Mutex, time::Duration};
+use tauri::State;
+use uuid::Uuid;
+
+// 等待 node shell pod 拉取镜像并启动的时间
+const NODE_SHELL_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NodeShell {
+    node: String,
+    namespace: Option<String>,
+    image: Option<String>,
+    // 宿主机上执行的命令，为空时自动探测 shell
+    command: Option<Vec<String>>,
+    cols: Option<u16>,
+    rows: Option<u16>,
+    binary: Option<bool>,
+}
+
+// pod 名必须符合 DNS-1123，节点名过长时截断
+fn node_shell_name(node: &str) -> String {
+    let suffix = &Uuid::new_v4().simple().to_string()[..5];
+    let node: String = node
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .take(63 - "node-shell-".len() - suffix.len() - 1)
+        .collect();
+    format!("node-shell-{}-{}", node.trim_end_matches('-'), suffix)
+}
+
+async fn run_node_shell(
+    pods: &Api<Pod>,
+    name: &str,
+    node_shell: &NodeShell,
+    ws_manager: WebsocketManager,
+    client_id: Uuid,
+    channel_id: String,
+) -> Result<(), MyError> {
+    tokio::time::timeout(
+        NODE_SHELL_TIMEOUT,
+        await_condition(pods.clone(), name, is_pod_running()),
+    )
+    .await
+    .map_err(|_| MyError::KubeError(format!("Timed out waiting for {}", name)))?
+    .map_err(|e| MyError::KubeError(format!("Node shell pod not ready: {}", e)))?;
+
+    let command = match &node_shell.command {
+        Some(command) => node_shell::nsenter_command(command),
+        None => {
+            let candidates: Vec<Vec<String>> = DEFAULT_SHELLS
+                .iter()
+                .map(|shell| {
+                    let shell: Vec<String> = shell.iter().map(|s| s.to_string()).collect();
+                    node_shell::nsenter_command(&shell)
+                })
+                .collect();
+            terminal::detect_shell(pods, name, "shell", &candidates).await?
+        }
+    };
+    let _ = ws_manager
+        .send_status(
+            client_id,
+            &channel_id,
+            serde_json::json!({ "pod": name, "node": node_shell.node }),
+        )
+        .await;
+
+    let attached = pods
+        .exec(
+            name,
+            command,
+            &AttachParams::interactive_tty().container("shell"),
+        )
+        .await
+        .map_err(terminal::exec_error)?;
+
+    let options = TerminalOptions {
+        binary: node_shell.binary.unwrap_or(false),
+        cols: node_shell.cols,
+        rows: node_shell.rows,
+        recorder: None,
+    };
+    terminal::run_session(ws_manager, client_id, channel_id, attached, options).await
+}
+
+/// 在节点上创建特权 pod 并进入宿主机 shell，会话结束后删除 pod
+#[tauri::command]
+pub async fn node_shell(
+    node_shell: NodeShell,
+    client_id: String,
+    channel_id: String,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<(), MyError> {
+    let (client, ws_manager) = {
+        let app_data = state.lock().unwrap();
+        (
+            app_data.client.clone().unwrap(),
+            app_data.websocket.clone().unwrap(),
+        )
+    };
+    let client_id = Uuid::parse_str(&client_id).map_err(|e| MyError::InvalidUuid(e.to_string()))?;
+    let namespace = node_shell
+        .namespace
+        .clone()
+        .unwrap_or_else(|| DEFAULT_NODE_SHELL_NAMESPACE.to_string());
+    let pods: Api<Pod> = Api::namespaced(client.clone(), &namespace);
+
+    let name = node_shell_name(&node_shell.node);
+    let pod = node_shell::node_shell_pod(
+        &name,
+        &node_shell.node,
+        node_shell
+            .image
+            .as_deref()
+            .unwrap_or(DEFAULT_NODE_SHELL_IMAGE),
+    )?;
+    pods.create(&PostParams::default(), &pod).await?;
+    state
+        .lock()
+        .unwrap()
+        .node_shells
+        .insert(name.clone(), (client.clone(), namespace.clone()));
+
+    let result = run_node_shell(&pods, &name, &node_shell, ws_manager, client_id, channel_id).await;
+
+    // 无论会话是否成功都删除 pod
+    state.lock().unwrap().node_shells.remove(&name);
+    node_shell::delete_node_shell(client, &namespace, &name).await;
+    result
+}
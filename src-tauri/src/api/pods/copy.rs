@@ -0,0 +1,111 @@
+use crate::{
+    boot::setup::AppData,
+    error::MyError,
+    handler::copy::{self, CopyTarget},
+    resource::copy::FileCopyResult,
+};
+use k8s_openapi::api::core::v1::Pod;
+use kube::api::Api;
+use serde::Deserialize;
+use std::{path::PathBuf, sync::Mutex};
+use tauri::{AppHandle, State};
+use tokio_util::sync::CancellationToken;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PodFileCopy {
+    // 由前端生成，用于进度事件和取消
+    id: String,
+    namespace: String,
+    name: String,
+    container: String,
+    // 下载时为容器内的文件或目录，上传时为容器内的目标目录
+    remote_path: String,
+    // 下载时为本地目标目录，上传时为本地文件或目录
+    local_path: String,
+}
+
+fn register(state: &State<'_, Mutex<AppData>>, id: &str) -> Result<CancellationToken, MyError> {
+    let mut app_data = state.lock().unwrap();
+    if app_data.file_copies.contains_key(id) {
+        return Err(MyError::OtherError(format!(
+            "File copy {} already running",
+            id
+        )));
+    }
+    let cancel = CancellationToken::new();
+    app_data.file_copies.insert(id.to_string(), cancel.clone());
+    Ok(cancel)
+}
+
+#[tauri::command]
+pub async fn copy_from_pod(
+    pod_file_copy: PodFileCopy,
+    app: AppHandle,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<FileCopyResult, MyError> {
+    let client = {
+        let app_data = state.lock().unwrap();
+        app_data.client.clone().unwrap()
+    };
+    let pods: Api<Pod> = Api::namespaced(client, &pod_file_copy.namespace);
+    let cancel = register(&state, &pod_file_copy.id)?;
+
+    let target = CopyTarget {
+        pods: &pods,
+        pod: &pod_file_copy.name,
+        container: &pod_file_copy.container,
+    };
+    let result = copy::copy_from_pod(
+        &app,
+        target,
+        &pod_file_copy.id,
+        &pod_file_copy.remote_path,
+        &PathBuf::from(&pod_file_copy.local_path),
+        &cancel,
+    )
+    .await;
+    state.lock().unwrap().file_copies.remove(&pod_file_copy.id);
+    result
+}
+
+#[tauri::command]
+pub async fn copy_to_pod(
+    pod_file_copy: PodFileCopy,
+    app: AppHandle,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<FileCopyResult, MyError> {
+    let client = {
+        let app_data = state.lock().unwrap();
+        app_data.client.clone().unwrap()
+    };
+    let pods: Api<Pod> = Api::namespaced(client, &pod_file_copy.namespace);
+    let cancel = register(&state, &pod_file_copy.id)?;
+
+    let target = CopyTarget {
+        pods: &pods,
+        pod: &pod_file_copy.name,
+        container: &pod_file_copy.container,
+    };
+    let result = copy::copy_to_pod(
+        &app,
+        target,
+        &pod_file_copy.id,
+        &PathBuf::from(&pod_file_copy.local_path),
+        &pod_file_copy.remote_path,
+        &cancel,
+    )
+    .await;
+    state.lock().unwrap().file_copies.remove(&pod_file_copy.id);
+    result
+}
+
+#[tauri::command]
+pub async fn cancel_file_copy(id: String, state: State<'_, Mutex<AppData>>) -> Result<(), MyError> {
+    let app_data = state.lock().unwrap();
+    let cancel = app_data
+        .file_copies
+        .get(&id)
+        .ok_or_else(|| MyError::OtherError(format!("File copy {} not found", id)))?;
+    cancel.cancel();
+    Ok(())
+}
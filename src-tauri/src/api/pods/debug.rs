@@ -0,0 +1,143 @@
+use crate::{
+    boot::setup::AppData,
+    error::MyError,
+    handler::terminal::{self, TerminalOptions},
+};
+use k8s_openapi::api::core::v1::Pod;
+use kube::{
+    api::{Api, AttachParams, Patch, PatchParams},
+    runtime::wait::await_condition,
+};
+use serde::Deserialize;
+use std::{sync::Mutex, time::Duration};
+use tauri::State;
+use uuid::Uuid;
+
+const DEFAULT_DEBUG_IMAGE: &str = "busybox:1.36";
+// 等待临时容器拉取镜像并启动的时间
+const DEBUG_CONTAINER_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PodDebug {
+    namespace: String,
+    name: String,
+    image: Option<String>,
+    // 共享该容器的进程命名空间，相当于 kubectl debug --target
+    target: Option<String>,
+    command: Option<Vec<String>>,
+    cols: Option<u16>,
+    rows: Option<u16>,
+    binary: Option<bool>,
+}
+
+// 临时容器是否已经运行，启动失败时返回原因
+fn ephemeral_container_state(pod: &Pod, container: &str) -> Option<Result<(), String>> {
+    let status = pod
+        .status
+        .as_ref()?
+        .ephemeral_container_statuses
+        .as_ref()?
+        .iter()
+        .find(|status| status.name == container)?;
+    let state = status.state.as_ref()?;
+    if state.running.is_some() {
+        return Some(Ok(()));
+    }
+    if let Some(terminated) = &state.terminated {
+        return Some(Err(format!(
+            "debug container terminated: {}",
+            terminated.reason.clone().unwrap_or_default()
+        )));
+    }
+    // 除了 ContainerCreating 之外的等待原因都意味着启动失败，例如拉取镜像失败或配置错误
+    match state.waiting.as_ref().and_then(|w| w.reason.as_deref()) {
+        None | Some("ContainerCreating") => None,
+        Some(reason) => Some(Err(format!("debug container failed to start: {}", reason))),
+    }
+}
+
+#[tauri::command]
+pub async fn debug_pod(
+    pod_debug: PodDebug,
+    client_id: String,
+    channel_id: String,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<(), MyError> {
+    let (client, ws_manager) = {
+        let app_data = state.lock().unwrap();
+        (
+            app_data.client.clone().unwrap(),
+            app_data.websocket.clone().unwrap(),
+        )
+    };
+    let client_id = Uuid::parse_str(&client_id).map_err(|e| MyError::InvalidUuid(e.to_string()))?;
+    let pods: Api<Pod> = Api::namespaced(client, &pod_debug.namespace);
+
+    let container = format!("debugger-{}", &Uuid::new_v4().simple().to_string()[..5]);
+    let mut debug_container = serde_json::json!({
+        "name": container,
+        "image": pod_debug.image.as_deref().unwrap_or(DEFAULT_DEBUG_IMAGE),
+        "stdin": true,
+        "tty": true,
+        "terminationMessagePolicy": "File",
+    });
+    if let Some(command) = &pod_debug.command {
+        debug_container["command"] = serde_json::json!(command);
+    }
+    if let Some(target) = &pod_debug.target {
+        debug_container["targetContainerName"] = serde_json::json!(target);
+    }
+    let patch = serde_json::json!({ "spec": { "ephemeralContainers": [debug_container] } });
+    pods.patch_ephemeral_containers(
+        &pod_debug.name,
+        &PatchParams::default(),
+        &Patch::Strategic(patch),
+    )
+    .await
+    .map_err(terminal::exec_error)?;
+
+    let condition_container = container.clone();
+    let pod = tokio::time::timeout(
+        DEBUG_CONTAINER_TIMEOUT,
+        await_condition(pods.clone(), &pod_debug.name, move |pod: Option<&Pod>| {
+            // pod 被删除时也结束等待
+            pod.is_none_or(|pod| ephemeral_container_state(pod, &condition_container).is_some())
+        }),
+    )
+    .await
+    .map_err(|_| MyError::KubeError(format!("Timed out waiting for {}", container)))?
+    .map_err(|e| MyError::KubeError(format!("Debug container not ready: {}", e)))?;
+    let Some(pod) = pod else {
+        return Err(MyError::KubeError(format!(
+            "Pod {} was deleted before {} started",
+            pod_debug.name, container
+        )));
+    };
+    if let Some(Err(reason)) = ephemeral_container_state(&pod, &container) {
+        return Err(MyError::KubeError(reason));
+    }
+
+    let _ = ws_manager
+        .send_status(
+            client_id,
+            &channel_id,
+            serde_json::json!({ "container": container }),
+        )
+        .await;
+
+    let attached = pods
+        .attach(
+            &pod_debug.name,
+            &AttachParams::interactive_tty().container(&container),
+        )
+        .await
+        .map_err(terminal::exec_error)?;
+
+    let options = TerminalOptions {
+        binary: pod_debug.binary.unwrap_or(false),
+        cols: pod_debug.cols,
+        rows: pod_debug.rows,
+        recorder: None,
+    };
+    terminal::run_session(ws_manager, client_id, channel_id, attached, options).await
+}
@@ -0,0 +1,21 @@
+use crate::{
+    boot::setup::AppData, error::MyError, handler::diagnostics,
+    resource::diagnostics::PodDiagnostics,
+};
+use std::sync::Mutex;
+use tauri::State;
+
+/// 根据容器状态、事件、节点和 PVC 分析 pod 未正常运行的原因
+#[tauri::command]
+pub async fn diagnose_pod(
+    namespace: String,
+    name: String,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<PodDiagnostics, MyError> {
+    let client = {
+        let app_data = state.lock().unwrap();
+        app_data.client.clone().unwrap()
+    };
+    let ctx = diagnostics::load_context(client, &namespace, &name).await?;
+    Ok(diagnostics::evaluate(&ctx))
+}
@@ -0,0 +1,72 @@
+use crate::{
+    boot::setup::AppData,
+    error::MyError,
+    handler::file_browser::{self, DEFAULT_READ_LIMIT},
+    resource::file::{FileContent, FileEntry},
+};
+use k8s_openapi::api::core::v1::Pod;
+use kube::api::Api;
+use serde::Deserialize;
+use std::sync::Mutex;
+use tauri::State;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PodFile {
+    namespace: String,
+    name: String,
+    container: String,
+    path: String,
+}
+
+fn pods_api(state: &State<'_, Mutex<AppData>>, namespace: &str) -> Api<Pod> {
+    let client = {
+        let app_data = state.lock().unwrap();
+        app_data.client.clone().unwrap()
+    };
+    Api::namespaced(client, namespace)
+}
+
+#[tauri::command]
+pub async fn list_pod_files(
+    pod_file: PodFile,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<Vec<FileEntry>, MyError> {
+    let pods = pods_api(&state, &pod_file.namespace);
+    file_browser::list_dir(&pods, &pod_file.name, &pod_file.container, &pod_file.path).await
+}
+
+#[tauri::command]
+pub async fn read_pod_file(
+    pod_file: PodFile,
+    // 最多读取的字节数，默认 1MB
+    limit: Option<usize>,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<FileContent, MyError> {
+    let pods = pods_api(&state, &pod_file.namespace);
+    file_browser::read_file(
+        &pods,
+        &pod_file.name,
+        &pod_file.container,
+        &pod_file.path,
+        limit.unwrap_or(DEFAULT_READ_LIMIT),
+    )
+    .await
+}
+
+// 上传本地文件或目录使用 copy_to_pod
+#[tauri::command]
+pub async fn write_pod_file(
+    pod_file: PodFile,
+    content: String,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<(), MyError> {
+    let pods = pods_api(&state, &pod_file.namespace);
+    file_browser::write_file(
+        &pods,
+        &pod_file.name,
+        &pod_file.container,
+        &pod_file.path,
+        content.into_bytes(),
+    )
+    .await
+}
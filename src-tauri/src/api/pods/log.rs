@@ -0,0 +1,96 @@
+use crate::{
+    boot::setup::AppData,
+    error::MyError,
+    handler::{
+        log::{self, LogExportFormat, LogSource},
+        workload::workload_pods,
+    },
+    resource::log::LogExportResult,
+};
+use k8s_openapi::{
+    api::core::v1::Pod,
+    chrono::{DateTime, Utc},
+};
+use kube::api::{Api, LogParams};
+use serde::Deserialize;
+use std::{path::PathBuf, sync::Mutex};
+use tauri::{AppHandle, State};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PodLogExport {
+    namespace: String,
+    // 为空或 Pod 时导出单个 Pod，否则按工作负载（Deployment 等）聚合导出
+    kind: Option<String>,
+    name: String,
+    container: Option<String>,
+    since: Option<i64>,
+    since_time: Option<DateTime<Utc>>,
+    timestamps: Option<bool>,
+    previous: Option<bool>,
+    path: String,
+    format: LogExportFormat,
+}
+
+fn container_sources(pod: &Pod) -> Vec<LogSource> {
+    let pod_name = pod.metadata.name.clone().unwrap_or_default();
+    pod.spec
+        .iter()
+        .flat_map(|spec| spec.containers.iter())
+        .map(|container| LogSource {
+            pod: pod_name.clone(),
+            container: container.name.clone(),
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub async fn export_logs(
+    pod_log_export: PodLogExport,
+    app: AppHandle,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<LogExportResult, MyError> {
+    let client = {
+        let app_data = state.lock().unwrap();
+        app_data.client.clone().unwrap()
+    };
+    let namespace = &pod_log_export.namespace;
+    let pods: Api<Pod> = Api::namespaced(client.clone(), namespace);
+
+    let sources = match pod_log_export.kind.as_deref() {
+        None | Some("Pod") | Some("pod") => match &pod_log_export.container {
+            Some(container) => vec![LogSource {
+                pod: pod_log_export.name.clone(),
+                container: container.clone(),
+            }],
+            None => container_sources(&pods.get(&pod_log_export.name).await?),
+        },
+        Some(kind) => workload_pods(client, namespace, kind, &pod_log_export.name)
+            .await?
+            .iter()
+            .flat_map(container_sources)
+            .filter(|source| {
+                pod_log_export
+                    .container
+                    .as_ref()
+                    .is_none_or(|container| &source.container == container)
+            })
+            .collect(),
+    };
+    if sources.is_empty() {
+        return Err(MyError::OtherError(format!(
+            "No containers found for {}",
+            pod_log_export.name
+        )));
+    }
+
+    let params = LogParams {
+        since_seconds: pod_log_export.since,
+        since_time: pod_log_export.since_time,
+        timestamps: pod_log_export.timestamps.unwrap_or(false),
+        previous: pod_log_export.previous.unwrap_or(false),
+        ..LogParams::default()
+    };
+    let path = PathBuf::from(&pod_log_export.path);
+
+    log::export_logs(&app, pods, sources, params, &path, pod_log_export.format).await
+}
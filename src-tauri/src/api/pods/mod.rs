@@ -1 +1,6 @@
+pub mod copy;
+pub mod debug;
+pub mod diagnostics;
+pub mod file;
+pub mod log;
 pub mod pod;
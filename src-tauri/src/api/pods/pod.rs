@@ -1,4 +1,4 @@
-use crate::boot::setup::AppData;
+use crate::boot::{setup::AppData, websocket::ChannelKind};
 use crate::error::MyError;
 use futures::TryStreamExt;
 use k8s_openapi::api::core::v1::Pod;
@@ -6,21 +6,58 @@ use kube::runtime::{watcher, WatchStreamExt};
 use kube::Api;
 use std::sync::Mutex;
 use tauri::State;
+use uuid::Uuid;
 
 #[tauri::command]
-pub async fn watch_pods(namespace: &str, state: State<'_, Mutex<AppData>>) -> Result<(), MyError> {
-    let client = {
+pub async fn watch_pods(
+    namespace: &str,
+    client_id: String,
+    channel_id: String,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<(), MyError> {
+    let (client, ws_manager) = {
         let app_data = state.lock().unwrap();
-        app_data.client.clone().unwrap()
+        (
+            app_data.client.clone().unwrap(),
+            app_data.websocket.clone().unwrap(),
+        )
     };
+    let client_id = Uuid::parse_str(&client_id).map_err(|e| MyError::InvalidUuid(e.to_string()))?;
+    let cancel = ws_manager
+        .open_channel(client_id, &channel_id, ChannelKind::Watch, None)
+        .await
+        .map_err(MyError::WebsocketError)?;
 
     let api = Api::<Pod>::namespaced(client, namespace);
-    watcher(api, watcher::Config::default())
+    let ws_manager_watch = ws_manager.clone();
+    let watch_channel = channel_id.clone();
+    let watch = watcher(api, watcher::Config::default())
         .default_backoff()
-        .try_for_each(|event| async move {
-            println!("{:?}", event);
-            Ok(())
-        })
-        .await?;
-    Ok(())
+        .map_err(MyError::from)
+        .try_for_each(|event| {
+            let ws_manager = ws_manager_watch.clone();
+            let channel_id = watch_channel.clone();
+            async move {
+                // 每个 watch 事件作为一行 JSON 发送
+                let (event_type, object) = match event {
+                    watcher::Event::Apply(pod) => ("apply", Some(pod)),
+                    watcher::Event::Delete(pod) => ("delete", Some(pod)),
+                    watcher::Event::Init => ("init", None),
+                    watcher::Event::InitApply(pod) => ("init_apply", Some(pod)),
+                    watcher::Event::InitDone => ("init_done", None),
+                };
+                let line = serde_json::json!({ "type": event_type, "object": object }).to_string();
+                ws_manager
+                    .send_event(client_id, &channel_id, line)
+                    .await
+                    .map_err(MyError::WebsocketError)
+            }
+        });
+
+    let result = tokio::select! {
+        result = watch => result,
+        _ = cancel.cancelled() => Ok(()),
+    };
+    ws_manager.close_channel(client_id, &channel_id).await;
+    result
 }
@@ -0,0 +1,87 @@
+use crate::{
+    boot::setup::AppData,
+    error::MyError,
+    handler::{
+        port_forward::PortForwardSpec,
+        port_forward_profile::{self, PortForwardProfile},
+    },
+    resource::port_forward::PortForwardInfo,
+};
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub async fn start_port_forward(
+    port_forward: PortForwardSpec,
+    app: AppHandle,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<PortForwardInfo, MyError> {
+    let (client, manager) = {
+        let app_data = state.lock().unwrap();
+        (
+            app_data.client.clone().unwrap(),
+            app_data.port_forward.clone(),
+        )
+    };
+    manager.start(app, client, port_forward, None).await
+}
+
+#[tauri::command]
+pub async fn stop_port_forward(
+    id: String,
+    app: AppHandle,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<(), MyError> {
+    let manager = {
+        let app_data = state.lock().unwrap();
+        app_data.port_forward.clone()
+    };
+    manager.stop(&app, &id)
+}
+
+#[tauri::command]
+pub async fn list_port_forwards(
+    state: State<'_, Mutex<AppData>>,
+) -> Result<Vec<PortForwardInfo>, MyError> {
+    let manager = {
+        let app_data = state.lock().unwrap();
+        app_data.port_forward.clone()
+    };
+    Ok(manager.list())
+}
+
+#[tauri::command]
+pub async fn list_port_forward_profiles(
+    app: AppHandle,
+) -> Result<Vec<PortForwardProfile>, MyError> {
+    port_forward_profile::load_profiles(&app)
+}
+
+#[tauri::command]
+pub async fn save_port_forward_profile(
+    profile: PortForwardProfile,
+    app: AppHandle,
+) -> Result<PortForwardProfile, MyError> {
+    port_forward_profile::save_profile(&app, profile)
+}
+
+#[tauri::command]
+pub async fn delete_port_forward_profile(id: String, app: AppHandle) -> Result<(), MyError> {
+    port_forward_profile::delete_profile(&app, &id)
+}
+
+#[tauri::command]
+pub async fn start_port_forward_profile(
+    id: String,
+    app: AppHandle,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<PortForwardInfo, MyError> {
+    let (kubeconfig, manager) = {
+        let app_data = state.lock().unwrap();
+        (
+            app_data.kubernetes_configs.clone(),
+            app_data.port_forward.clone(),
+        )
+    };
+    port_forward_profile::start_profile(&app, &manager, kubeconfig, &id).await
+}
@@ -0,0 +1,153 @@
+use crate::{
+    boot::{
+        setup::AppData,
+        websocket::{ChannelKind, StdStream},
+    },
+    error::MyError,
+    handler::recording::{self, CastEvent},
+    resource::recording::RecordingInfo,
+};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    sync::Mutex,
+    time::Duration,
+};
+use tauri::State;
+use tokio::select;
+use uuid::Uuid;
+
+#[tauri::command]
+pub async fn list_recordings() -> Result<Vec<RecordingInfo>, MyError> {
+    let mut recordings = Vec::new();
+    for entry in std::fs::read_dir(recording::recordings_dir()?)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("cast") {
+            continue;
+        }
+        let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        // 跳过头部损坏的文件
+        let header = match File::open(&path)
+            .map_err(MyError::from)
+            .and_then(|file| recording::read_header(&mut BufReader::new(file)))
+        {
+            Ok(header) => header,
+            Err(e) => {
+                tracing::warn!("Skipping recording {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        recordings.push(RecordingInfo {
+            id: id.to_string(),
+            path: path.to_string_lossy().to_string(),
+            title: header.title,
+            timestamp: header.timestamp,
+            width: header.width,
+            height: header.height,
+            size: std::fs::metadata(&path)?.len(),
+        });
+    }
+    recordings.sort_by_key(|recording| std::cmp::Reverse(recording.timestamp));
+    Ok(recordings)
+}
+
+#[tauri::command]
+pub async fn delete_recording(id: String) -> Result<(), MyError> {
+    std::fs::remove_file(recording::recording_path(&id)?)?;
+    Ok(())
+}
+
+/// 按录制时的节奏把输出回放到 websocket channel，终端大小变化以 status 帧发送
+#[tauri::command]
+pub async fn replay_recording(
+    id: String,
+    // 回放倍速，默认 1.0
+    speed: Option<f64>,
+    // 两个事件之间的最长等待时间（秒），压缩录制中的空闲
+    max_idle: Option<f64>,
+    client_id: String,
+    channel_id: String,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<(), MyError> {
+    let ws_manager = {
+        let app_data = state.lock().unwrap();
+        app_data.websocket.clone().unwrap()
+    };
+    let client_id = Uuid::parse_str(&client_id).map_err(|e| MyError::InvalidUuid(e.to_string()))?;
+    let speed = speed.filter(|speed| *speed > 0.0).unwrap_or(1.0);
+
+    let (header, reader) = recording::open_recording(&id)?;
+    let cancel = ws_manager
+        .open_channel(client_id, &channel_id, ChannelKind::Replay, None)
+        .await
+        .map_err(MyError::WebsocketError)?;
+    let _ = ws_manager
+        .send_status(
+            client_id,
+            &channel_id,
+            serde_json::json!({
+                "title": header.title,
+                "cols": header.width,
+                "rows": header.height,
+            }),
+        )
+        .await;
+
+    let mut last = 0.0;
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (time, code, data): CastEvent = match serde_json::from_str(&line) {
+            Ok(event) => event,
+            Err(e) => {
+                tracing::warn!("Skipping invalid recording event: {}", e);
+                continue;
+            }
+        };
+        let mut delay = (time - last).max(0.0);
+        if let Some(max_idle) = max_idle {
+            delay = delay.min(max_idle);
+        }
+        last = time;
+        select! {
+            _ = tokio::time::sleep(Duration::from_secs_f64(delay / speed)) => {}
+            _ = cancel.cancelled() => break,
+        }
+
+        let result = match code.as_str() {
+            "o" => {
+                ws_manager
+                    .send_text(client_id, &channel_id, StdStream::Stdout, data)
+                    .await
+            }
+            "r" => match data.split_once('x') {
+                Some((cols, rows)) => {
+                    ws_manager
+                        .send_status(
+                            client_id,
+                            &channel_id,
+                            serde_json::json!({
+                                "cols": cols.parse::<u16>().ok(),
+                                "rows": rows.parse::<u16>().ok(),
+                            }),
+                        )
+                        .await
+                }
+                None => Ok(()),
+            },
+            // 输入事件已经体现在回显里，不单独回放
+            _ => Ok(()),
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to send replay event: {}", e);
+            break;
+        }
+    }
+
+    ws_manager.close_channel(client_id, &channel_id).await;
+    Ok(())
+}
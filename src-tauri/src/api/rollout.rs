@@ -0,0 +1,156 @@
+use crate::{
+    boot::{setup::AppData, websocket::ChannelKind},
+    error::MyError,
+    handler::rollout::{self, RolloutKind},
+    resource::rollout::{RolloutPhase, RolloutProgress, RolloutResult, RolloutRevision},
+};
+use futures::TryStreamExt;
+use std::{sync::Mutex, time::Duration};
+use tauri::State;
+use tokio::select;
+use uuid::Uuid;
+
+#[tauri::command]
+pub async fn rollout_restart(
+    namespace: String,
+    kind: String,
+    name: String,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<RolloutResult, MyError> {
+    let client = {
+        let app_data = state.lock().unwrap();
+        app_data.client.clone().unwrap()
+    };
+    rollout::restart(client, &namespace, RolloutKind::parse(&kind)?, &name).await
+}
+
+#[tauri::command]
+pub async fn rollout_pause(
+    namespace: String,
+    name: String,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<RolloutResult, MyError> {
+    let client = {
+        let app_data = state.lock().unwrap();
+        app_data.client.clone().unwrap()
+    };
+    rollout::set_paused(client, &namespace, &name, true).await
+}
+
+#[tauri::command]
+pub async fn rollout_resume(
+    namespace: String,
+    name: String,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<RolloutResult, MyError> {
+    let client = {
+        let app_data = state.lock().unwrap();
+        app_data.client.clone().unwrap()
+    };
+    rollout::set_paused(client, &namespace, &name, false).await
+}
+
+#[tauri::command]
+pub async fn rollout_undo(
+    namespace: String,
+    name: String,
+    // 为空时回滚到上一个修订
+    revision: Option<i64>,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<RolloutResult, MyError> {
+    let client = {
+        let app_data = state.lock().unwrap();
+        app_data.client.clone().unwrap()
+    };
+    rollout::undo(client, &namespace, &name, revision).await
+}
+
+#[tauri::command]
+pub async fn rollout_history(
+    namespace: String,
+    kind: String,
+    name: String,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<Vec<RolloutRevision>, MyError> {
+    let client = {
+        let app_data = state.lock().unwrap();
+        app_data.client.clone().unwrap()
+    };
+    rollout::history(client, &namespace, RolloutKind::parse(&kind)?, &name).await
+}
+
+/// 在 websocket channel 上推送滚动更新进度，最终状态以 status 帧发送，之后关闭 channel 并返回最终状态
+#[tauri::command]
+pub async fn rollout_status(
+    namespace: String,
+    kind: String,
+    name: String,
+    // 超时秒数，超时视为失败
+    timeout: Option<u64>,
+    client_id: String,
+    channel_id: String,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<Option<RolloutProgress>, MyError> {
+    let (client, ws_manager) = {
+        let app_data = state.lock().unwrap();
+        (
+            app_data.client.clone().unwrap(),
+            app_data.websocket.clone().unwrap(),
+        )
+    };
+    let client_id = Uuid::parse_str(&client_id).map_err(|e| MyError::InvalidUuid(e.to_string()))?;
+    let kind = RolloutKind::parse(&kind)?;
+    let cancel = ws_manager
+        .open_channel(client_id, &channel_id, ChannelKind::Watch, None)
+        .await
+        .map_err(MyError::WebsocketError)?;
+
+    let mut updates = rollout::rollout_status(client, &namespace, kind, &name);
+    let deadline = tokio::time::sleep(timeout.map_or(Duration::MAX, Duration::from_secs));
+    tokio::pin!(deadline);
+    let mut last: Option<RolloutProgress> = None;
+    let result = loop {
+        let next = select! {
+            _ = cancel.cancelled() => break Ok(last),
+            _ = &mut deadline => {
+                let Some(mut timed_out) = last.take() else {
+                    break Err(MyError::OtherError(format!("Timed out waiting for {}", name)));
+                };
+                timed_out.phase = RolloutPhase::Failed;
+                timed_out.message =
+                    format!("timed out waiting for the condition: {}", timed_out.message);
+                let status = serde_json::to_value(&timed_out).unwrap_or_default();
+                let _ = ws_manager.send_status(client_id, &channel_id, status).await;
+                break Ok(Some(timed_out));
+            }
+            next = updates.try_next() => next,
+        };
+        let progress = match next {
+            Ok(Some(progress)) => progress,
+            Ok(None) => break Ok(last),
+            Err(e) => break Err(e),
+        };
+        // 无关字段的变化也会触发事件，相同进度不重复发送
+        if last.as_ref() == Some(&progress) {
+            continue;
+        }
+        // 最终状态作为控制帧发送，不会因为队列满被丢弃
+        let finished = progress.phase != RolloutPhase::Progressing;
+        let sent = if finished {
+            let status = serde_json::to_value(&progress).unwrap_or_default();
+            ws_manager.send_status(client_id, &channel_id, status).await
+        } else {
+            let line = serde_json::to_string(&progress).unwrap_or_default();
+            ws_manager.send_line(client_id, &channel_id, line).await
+        };
+        if let Err(e) = sent {
+            break Err(MyError::WebsocketError(e));
+        }
+        last = Some(progress);
+        if finished {
+            break Ok(last);
+        }
+    };
+    ws_manager.close_channel(client_id, &channel_id).await;
+    result
+}
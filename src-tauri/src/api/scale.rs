@@ -0,0 +1,52 @@
+use crate::{boot::setup::AppData, error::MyError, handler::scale, resource::scale::ScaleResult};
+use std::sync::Mutex;
+use tauri::State;
+
+/// 通过 scale 子资源修改副本数，支持在 discovery 中暴露了 scale 的 CRD
+#[tauri::command]
+pub async fn scale_workload(
+    namespace: String,
+    kind: String,
+    name: String,
+    // 同名 kind 存在于多个 API 组时用于区分
+    group: Option<String>,
+    replicas: i32,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<ScaleResult, MyError> {
+    let (client, resource) = {
+        let app_data = state.lock().unwrap();
+        let discovery = app_data
+            .discovery
+            .as_ref()
+            .ok_or_else(|| MyError::NoClient("Discovery not available".to_string()))?;
+        (
+            app_data.client.clone().unwrap(),
+            scale::scalable_resource(discovery, group.as_deref(), &kind)?,
+        )
+    };
+    scale::scale(client, &namespace, &resource, &name, replicas).await
+}
+
+#[tauri::command]
+pub async fn scale_namespace_to_zero(
+    namespace: String,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<Vec<ScaleResult>, MyError> {
+    let client = {
+        let app_data = state.lock().unwrap();
+        app_data.client.clone().unwrap()
+    };
+    scale::scale_namespace_to_zero(client, &namespace).await
+}
+
+#[tauri::command]
+pub async fn restore_namespace_scale(
+    namespace: String,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<Vec<ScaleResult>, MyError> {
+    let client = {
+        let app_data = state.lock().unwrap();
+        app_data.client.clone().unwrap()
+    };
+    scale::restore_namespace(client, &namespace).await
+}
@@ -1,4 +1,12 @@
-use crate::{boot::setup::AppData, error::MyError};
+use crate::{
+    boot::{setup::AppData, websocket::ChannelKind},
+    error::MyError,
+    handler::{
+        recording::Recorder,
+        terminal::{self, TerminalOptions, DEFAULT_SHELLS},
+    },
+    resource::websocket::{ChannelInfo, ClientStats, WebsocketInfo},
+};
 use futures::{AsyncBufReadExt, TryStreamExt};
 use k8s_openapi::{
     api::core::v1::Pod,
@@ -12,10 +20,7 @@ use kube::{
 use serde::Deserialize;
 use std::sync::Mutex;
 use tauri::State;
-use tokio::{
-    io::{AsyncReadExt, AsyncWriteExt},
-    select,
-};
+use tokio::select;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Deserialize)]
@@ -34,6 +39,7 @@ pub struct PodLogStream {
 pub async fn log_stream(
     pod_log_stream: PodLogStream,
     client_id: String,
+    channel_id: String,
     state: State<'_, Mutex<AppData>>,
 ) -> Result<(), MyError> {
     let (client, ws_manager) = {
@@ -43,6 +49,7 @@ pub async fn log_stream(
             app_data.websocket.clone().unwrap(),
         )
     };
+    let client_id = Uuid::parse_str(&client_id).map_err(|e| MyError::InvalidUuid(e.to_string()))?;
 
     let pods: Api<Pod> = Api::namespaced(client, &pod_log_stream.namespace);
     let logs = pods
@@ -59,14 +66,21 @@ pub async fn log_stream(
             },
         )
         .await?;
-    let client_id = Uuid::parse_str(&client_id).map_err(|e| MyError::InvalidUuid(e.to_string()))?;
+    let cancel = ws_manager
+        .open_channel(client_id, &channel_id, ChannelKind::Log, None)
+        .await
+        .map_err(MyError::WebsocketError)?;
 
     let mut lines = logs.lines();
     loop {
-        match lines.try_next().await {
+        let next = select! {
+            _ = cancel.cancelled() => break,
+            next = lines.try_next() => next,
+        };
+        match next {
             std::result::Result::Ok(Some(line)) => {
                 // 直接发送日志行给WebSocket客户端
-                match ws_manager.send_message(client_id, line).await {
+                match ws_manager.send_line(client_id, &channel_id, line).await {
                     std::result::Result::Ok(_) => {}
                     std::result::Result::Err(e) => {
                         eprintln!("Failed to send log message: {}", e);
@@ -85,6 +99,7 @@ pub async fn log_stream(
         }
     }
 
+    ws_manager.close_channel(client_id, &channel_id).await;
     Ok(())
 }
 
@@ -93,13 +108,25 @@ pub struct PodTerminalStream {
     namespace: String,
     name: String,
     container: String,
-    command: Vec<String>,
+    // 为空时按 shells（默认 DEFAULT_SHELLS）顺序探测可用的 shell
+    command: Option<Vec<String>>,
+    shells: Option<Vec<Vec<String>>>,
+    // 打开终端时的初始大小
+    cols: Option<u16>,
+    rows: Option<u16>,
+    // 默认 tty 模式；非 tty 模式下 stderr 单独转发
+    tty: Option<bool>,
+    // 以二进制帧转发输入输出
+    binary: Option<bool>,
+    // 以 asciinema v2 格式录制会话
+    record: Option<bool>,
 }
 
 #[tauri::command]
 pub async fn pod_terminal(
     pod_terminal: PodTerminalStream,
     client_id: String,
+    channel_id: String,
     state: State<'_, Mutex<AppData>>,
 ) -> Result<(), MyError> {
     let (client, ws_manager) = {
@@ -117,118 +144,192 @@ pub async fn pod_terminal(
         .await
         .map_err(|e| MyError::KubeError(format!("Pod not ready: {}", e)))?;
 
-    let mut attached = pods
+    let command = match pod_terminal.command {
+        Some(command) if !command.is_empty() => command,
+        _ => {
+            let candidates = pod_terminal.shells.unwrap_or_else(|| {
+                DEFAULT_SHELLS
+                    .iter()
+                    .map(|shell| shell.iter().map(|arg| arg.to_string()).collect())
+                    .collect()
+            });
+            terminal::detect_shell(
+                &pods,
+                &pod_terminal.name,
+                &pod_terminal.container,
+                &candidates,
+            )
+            .await?
+        }
+    };
+    let _ = ws_manager
+        .send_status(
+            client_id,
+            &channel_id,
+            serde_json::json!({ "shell": command }),
+        )
+        .await;
+
+    let tty = pod_terminal.tty.unwrap_or(true);
+    let attached = pods
         .exec(
             &pod_terminal.name,
-            &pod_terminal.command,
+            &command,
             &AttachParams::default()
                 .stdin(true)
-                .stderr(false)
-                .tty(true)
+                .stderr(!tty)
+                .tty(tty)
                 .container(&pod_terminal.container),
         )
         .await
-        .map_err(|e| {
-            println!("Failed to exec into pod: {}", e);
-            MyError::KubeError(format!("Failed to exec into pod: {}", e))
-        })?;
-
-    let mut stdout = attached
-        .stdout()
-        .ok_or_else(|| MyError::KubeError("No stdout available".to_string()))?;
-    let mut stdin = attached
-        .stdin()
-        .ok_or_else(|| MyError::KubeError("No stdin available".to_string()))?;
-
-    let (input_tx, mut input_rx) = tokio::sync::mpsc::unbounded_channel::<String>();
-
-    ws_manager.set_terminal_input(client_id, input_tx).await;
-
-    let ws_manager_stdout = ws_manager.clone();
-    let stdout_task = tokio::spawn(async move {
-        let mut buffer = [0u8; 4096];
-        loop {
-            match stdout.read(&mut buffer).await {
-                std::result::Result::Ok(0) => break, // EOF
-                std::result::Result::Ok(n) => {
-                    let output = String::from_utf8_lossy(&buffer[..n]);
-                    if let std::result::Result::Err(e) = ws_manager_stdout
-                        .send_message(client_id, output.to_string())
-                        .await
-                    {
-                        eprintln!("Failed to send stdout: {}", e);
-                        break;
-                    }
-                }
-                std::result::Result::Err(e) => {
-                    eprintln!("Error reading stdout: {}", e);
-                    break;
-                }
-            }
-        }
-    });
+        .map_err(terminal::exec_error)?;
 
-    let stdin_task = tokio::spawn(async move {
-        while let Some(input) = input_rx.recv().await {
-            if let std::result::Result::Err(e) = stdin.write_all(input.as_bytes()).await {
-                eprintln!("Failed to write to stdin: {}", e);
-                break;
-            }
-            if let std::result::Result::Err(e) = stdin.flush().await {
-                eprintln!("Failed to flush stdin: {}", e);
-                break;
-            }
-        }
-    });
+    let recorder = if pod_terminal.record.unwrap_or(false) {
+        let title = format!(
+            "{}-{}-{}",
+            pod_terminal.namespace, pod_terminal.name, pod_terminal.container
+        );
+        let recorder = Recorder::create(&title, pod_terminal.cols, pod_terminal.rows, &command)?;
+        let _ = ws_manager
+            .send_status(
+                client_id,
+                &channel_id,
+                serde_json::json!({ "recording": recorder.id() }),
+            )
+            .await;
+        Some(recorder)
+    } else {
+        None
+    };
 
-    select! {
-        _ = stdout_task => {
-            println!("Stdout task completed for client {}", client_id);
-        }
-        _ = stdin_task => {
-            println!("Stdin task completed for client {}", client_id);
-        }
+    let options = TerminalOptions {
+        binary: pod_terminal.binary.unwrap_or(false),
+        cols: pod_terminal.cols,
+        rows: pod_terminal.rows,
+        recorder,
+    };
+    terminal::run_session(ws_manager, client_id, channel_id, attached, options).await
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PodAttachStream {
+    namespace: String,
+    name: String,
+    // 为空时使用 kubectl.kubernetes.io/default-container 注解指定的容器或第一个容器
+    container: Option<String>,
+    cols: Option<u16>,
+    rows: Option<u16>,
+    binary: Option<bool>,
+}
+
+// 按容器 spec 中的 stdin/tty 决定 attach 参数，和 kubectl attach 一致
+fn attach_params(pod: &Pod, container: Option<&str>) -> Result<AttachParams, MyError> {
+    let spec = pod
+        .spec
+        .as_ref()
+        .ok_or_else(|| MyError::KubeError("Pod has no spec".to_string()))?;
+    let default_container = pod
+        .metadata
+        .annotations
+        .as_ref()
+        .and_then(|annotations| annotations.get("kubectl.kubernetes.io/default-container"))
+        .map(String::as_str);
+    let name = container.or(default_container);
+    let container = match name {
+        Some(name) => spec.containers.iter().find(|c| c.name == name),
+        None => spec.containers.first(),
     }
+    .ok_or_else(|| {
+        MyError::KubeError(format!("Container {} not found", name.unwrap_or_default()))
+    })?;
 
-    ws_manager.remove_terminal_input(client_id).await;
+    let stdin = container.stdin.unwrap_or(false);
+    let tty = stdin && container.tty.unwrap_or(false);
+    Ok(AttachParams::default()
+        .container(&container.name)
+        .stdin(stdin)
+        .stdout(true)
+        .stderr(!tty)
+        .tty(tty))
+}
 
-    Ok(())
+/// attach 到容器的主进程，相当于 kubectl attach
+#[tauri::command]
+pub async fn pod_attach(
+    pod_attach: PodAttachStream,
+    client_id: String,
+    channel_id: String,
+    state: State<'_, Mutex<AppData>>,
+) -> Result<(), MyError> {
+    let (client, ws_manager) = {
+        let app_data = state.lock().unwrap();
+        (
+            app_data.client.clone().unwrap(),
+            app_data.websocket.clone().unwrap(),
+        )
+    };
+    let client_id = Uuid::parse_str(&client_id).map_err(|e| MyError::InvalidUuid(e.to_string()))?;
+
+    let pods: Api<Pod> = Api::namespaced(client, &pod_attach.namespace);
+
+    let pod = await_condition(pods.clone(), &pod_attach.name, is_pod_running())
+        .await
+        .map_err(|e| MyError::KubeError(format!("Pod not ready: {}", e)))?
+        .ok_or_else(|| MyError::KubeError(format!("Pod {} not found", pod_attach.name)))?;
+    let params = attach_params(&pod, pod_attach.container.as_deref())?;
+    let _ = ws_manager
+        .send_status(
+            client_id,
+            &channel_id,
+            serde_json::json!({
+                "container": params.container,
+                "stdin": params.stdin,
+                "tty": params.tty,
+            }),
+        )
+        .await;
+
+    let attached = pods
+        .attach(&pod_attach.name, &params)
+        .await
+        .map_err(terminal::exec_error)?;
+
+    let options = TerminalOptions {
+        binary: pod_attach.binary.unwrap_or(false),
+        cols: pod_attach.cols,
+        rows: pod_attach.rows,
+        recorder: None,
+    };
+    terminal::run_session(ws_manager, client_id, channel_id, attached, options).await
 }
 
-// #[derive(Debug, Clone, Deserialize)]
-// pub struct TerminalResize {
-//     namespace: String,
-//     name: String,
-//     container: String,
-//     rows: u16,
-//     cols: u16,
-// }
-
-// #[tauri::command]
-// pub async fn resize_terminal(
-//     resize_params: TerminalResize,
-//     state: State<'_, Mutex<AppData>>,
-// ) -> Result<(), MyError> {
-//     let client = {
-//         let app_data = state.lock().unwrap();
-//         app_data.client.clone().unwrap()
-//     };
-
-//     let _pods: Api<Pod> = Api::namespaced(client, &resize_params.namespace);
-
-//     // 创建终端大小参数
-//     let _terminal_size = TerminalSize {
-//         height: resize_params.rows,
-//         width: resize_params.cols,
-//     };
-
-//     // 注意：这个功能需要根据实际的 kube-rs API 调整
-//     // 目前 kube-rs 可能不直接支持调整终端大小
-//     // 这里提供一个基本框架
-//     println!(
-//         "Terminal resize requested for pod {}/{} to {}x{}",
-//         resize_params.namespace, resize_params.name, resize_params.cols, resize_params.rows
-//     );
-
-//     Ok(())
-// }
+#[tauri::command]
+pub async fn websocket_info(state: State<'_, Mutex<AppData>>) -> Result<WebsocketInfo, MyError> {
+    let ws_manager = {
+        let app_data = state.lock().unwrap();
+        app_data.websocket.clone().unwrap()
+    };
+    Ok(ws_manager.info())
+}
+
+#[tauri::command]
+pub async fn websocket_stats(
+    state: State<'_, Mutex<AppData>>,
+) -> Result<Vec<ClientStats>, MyError> {
+    let ws_manager = {
+        let app_data = state.lock().unwrap();
+        app_data.websocket.clone().unwrap()
+    };
+    Ok(ws_manager.stats().await)
+}
+
+#[tauri::command]
+pub async fn websocket_channels(
+    state: State<'_, Mutex<AppData>>,
+) -> Result<Vec<ChannelInfo>, MyError> {
+    let ws_manager = {
+        let app_data = state.lock().unwrap();
+        app_data.websocket.clone().unwrap()
+    };
+    Ok(ws_manager.channels().await)
+}
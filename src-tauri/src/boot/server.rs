@@ -1,4 +1,11 @@
-use crate::api::{cluster, k8s_proxy, pods::pod, websocket};
+use crate::{
+    api::{
+        cluster, delete, finalizer, graph, k8s_proxy, namespace, node,
+        pods::{copy, debug, diagnostics, file, log, pod},
+        port_forward, recording, rollout, scale, websocket,
+    },
+    handler::node_shell,
+};
 
 use super::setup;
 
@@ -23,10 +30,56 @@ pub fn run() {
             cluster::switch_cluster,
             cluster::list_clusters,
             pod::watch_pods,
+            log::export_logs,
+            debug::debug_pod,
+            copy::copy_from_pod,
+            copy::copy_to_pod,
+            copy::cancel_file_copy,
+            file::list_pod_files,
+            file::read_pod_file,
+            file::write_pod_file,
+            node::node_shell,
             k8s_proxy::proxy_request,
             websocket::log_stream,
             websocket::pod_terminal,
+            websocket::pod_attach,
+            websocket::websocket_info,
+            websocket::websocket_stats,
+            websocket::websocket_channels,
+            port_forward::start_port_forward,
+            port_forward::stop_port_forward,
+            port_forward::list_port_forwards,
+            port_forward::list_port_forward_profiles,
+            port_forward::save_port_forward_profile,
+            port_forward::delete_port_forward_profile,
+            port_forward::start_port_forward_profile,
+            rollout::rollout_restart,
+            rollout::rollout_pause,
+            rollout::rollout_resume,
+            rollout::rollout_undo,
+            rollout::rollout_status,
+            rollout::rollout_history,
+            scale::scale_workload,
+            scale::scale_namespace_to_zero,
+            scale::restore_namespace_scale,
+            namespace::sleep_namespace,
+            namespace::wake_namespace,
+            namespace::namespace_sleep_state,
+            delete::delete_preview,
+            delete::delete_resource,
+            finalizer::finalizer_diagnostics,
+            finalizer::remove_finalizers,
+            graph::resource_graph,
+            diagnostics::diagnose_pod,
+            recording::list_recordings,
+            recording::replay_recording,
+            recording::delete_recording,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                node_shell::cleanup_node_shells(app);
+            }
+        });
 }
@@ -1,16 +1,16 @@
 use crate::tray::create_tray;
 use kube::config::Kubeconfig;
-use std::sync::Mutex;
+use std::{collections::HashMap, sync::Mutex};
 use tauri::{Manager, WebviewUrl, WebviewWindowBuilder};
 
 #[cfg(target_os = "macos")]
 use tauri::TitleBarStyle;
 use tauri_plugin_updater::UpdaterExt;
 use tokio::runtime;
+use tokio_util::sync::CancellationToken;
 
 use super::websocket;
-
-const WEBSOCKET_PORT: u16 = 38012;
+use crate::handler::{graph::GraphCache, port_forward::PortForwardManager, port_forward_profile};
 
 #[derive(Default)]
 pub struct AppData {
@@ -18,6 +18,13 @@ pub struct AppData {
     pub client: Option<kube::Client>,
     pub discovery: Option<kube::Discovery>,
     pub websocket: Option<websocket::WebsocketManager>,
+    // 已创建的 node shell pod：pod 名 -> (client, namespace)，应用退出时清理
+    pub node_shells: HashMap<String, (kube::Client, String)>,
+    pub port_forward: PortForwardManager,
+    // 进行中的文件复制，用于取消
+    pub file_copies: HashMap<String, CancellationToken>,
+    // 关系图使用的资源缓存
+    pub graph_cache: GraphCache,
 }
 
 impl AppData {
@@ -27,6 +34,10 @@ impl AppData {
             client: None,
             discovery: None,
             websocket: Some(websocket::WebsocketManager::new()),
+            node_shells: HashMap::new(),
+            port_forward: PortForwardManager::default(),
+            file_copies: HashMap::new(),
+            graph_cache: GraphCache::default(),
         }
     }
 }
@@ -37,13 +48,14 @@ pub fn init(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
         let app_data = AppData::new();
         // Start websocket server
         if let Some(ws) = &app_data.websocket {
+            let listener = ws.bind().await?;
             let ws_clone = ws.clone();
             tokio::spawn(async move {
-                ws_clone.start_server(WEBSOCKET_PORT).await;
+                ws_clone.start_server(listener).await;
             });
         }
-        app_data
-    });
+        Ok::<_, std::io::Error>(app_data)
+    })?;
 
     // Manage both the AppData and Runtime
     app.manage(Mutex::new(app_data));
@@ -54,6 +66,9 @@ pub fn init(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
         create_tray(app.handle())?;
     }
 
+    // 托盘监听端口转发状态，自动启动放在托盘创建之后
+    tauri::async_runtime::spawn(port_forward_profile::start_on_launch(app.handle().clone()));
+
     let core_window = {
         let mut builder = WebviewWindowBuilder::new(app, "core", WebviewUrl::default())
             .title("Kubernetes manger");
@@ -1,53 +1,550 @@
 use arc_swap::ArcSwap;
-use futures::{SinkExt, StreamExt};
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use futures::{stream::SplitStream, SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicBool, AtomicU16, AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 use tokio::{
     net::{TcpListener, TcpStream},
-    sync::{mpsc, Mutex},
+    sync::{mpsc, Mutex, Notify},
+    time::MissedTickBehavior,
+};
+use tokio_tungstenite::tungstenite::{
+    handshake::server::{ErrorResponse, Request, Response},
+    http::StatusCode,
+    Message,
 };
-use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
-type Sender = mpsc::UnboundedSender<Message>;
-type ClientMap = Arc<Mutex<HashMap<Uuid, Sender>>>;
-type TerminalInputSender = mpsc::UnboundedSender<String>;
-type TerminalInputMap = Arc<Mutex<HashMap<Uuid, TerminalInputSender>>>;
+use crate::resource::websocket::{ChannelInfo, ClientStats, WebsocketInfo};
+
+type ClientMap = Arc<Mutex<HashMap<Uuid, Arc<ClientQueue>>>>;
+type ChannelInputSender = mpsc::UnboundedSender<ChannelInput>;
+type ChannelMap = Arc<Mutex<HashMap<(Uuid, String), Channel>>>;
+
+// 每个客户端最多排队的消息数，超出后按 OverflowPolicy 处理
+const QUEUE_CAPACITY: usize = 1024;
+// 合并后单个 websocket 帧的最大字节数
+const MAX_FRAME_BYTES: usize = 64 * 1024;
+// 每个 tick 最多向客户端发送一批消息
+const FLUSH_INTERVAL: Duration = Duration::from_millis(50);
+// webview 中允许连接的 Origin，非浏览器客户端（tauri websocket 插件）不带 Origin
+const ALLOWED_ORIGINS: &[&str] = &[
+    "tauri://localhost",
+    "http://tauri.localhost",
+    "https://tauri.localhost",
+    "http://localhost:1420",
+];
+// 握手协议版本，客户端 hello 中的版本不一致时拒绝连接
+const PROTOCOL_VERSION: u32 = 1;
+// 连接建立后等待客户端 hello 的时间
+const HELLO_TIMEOUT: Duration = Duration::from_secs(10);
+// 连接断开后保留会话的时间，期间客户端可以用 session_id 恢复
+const SESSION_GRACE: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    // 丢弃最旧的消息，适合日志这类可以丢行的流
+    DropOldest,
+    // 合并到队尾的消息中，无法合并时等待空位，适合终端输出、watch 事件这类不能丢数据的流
+    Coalesce,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChannelKind {
+    Log,
+    Exec,
+    Watch,
+    Replay,
+}
+
+// 客户端通过 channel 发给流的输入
+#[derive(Debug, Clone)]
+pub enum ChannelInput {
+    Data(Vec<u8>),
+    Resize { cols: u16, rows: u16 },
+}
+
+struct Channel {
+    kind: ChannelKind,
+    input: Option<ChannelInputSender>,
+    cancel: CancellationToken,
+}
+
+// 输出所属的标准流，二进制帧中占一个字节
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StdStream {
+    Stdin = 0,
+    Stdout = 1,
+    Stderr = 2,
+}
+
+// 服务端发给客户端的文本帧，同一连接上的多个流通过 channel 区分
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerFrame<'a> {
+    Data {
+        channel: &'a str,
+        data: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        stream: Option<StdStream>,
+    },
+    Closed {
+        channel: &'a str,
+    },
+    // 流的附加信息，例如终端实际使用的 shell
+    Status {
+        channel: &'a str,
+        status: &'a serde_json::Value,
+    },
+    Welcome {
+        session_id: String,
+        version: u32,
+        resumed: bool,
+        capabilities: Vec<ChannelKind>,
+    },
+    Error {
+        message: &'a str,
+    },
+}
+
+impl ServerFrame<'_> {
+    fn to_message(&self) -> Message {
+        Message::Text(serde_json::to_string(self).unwrap_or_default().into())
+    }
+}
+
+// 二进制帧格式：[channel 长度 u8][channel][stream u8][数据]，数据原样转发不做编码转换
+fn encode_binary_frame(channel: &str, stream: StdStream, data: &[u8]) -> Message {
+    let mut frame = Vec::with_capacity(channel.len() + data.len() + 2);
+    frame.push(channel.len() as u8);
+    frame.extend_from_slice(channel.as_bytes());
+    frame.push(stream as u8);
+    frame.extend_from_slice(data);
+    Message::Binary(frame.into())
+}
+
+fn decode_binary_frame(frame: &[u8]) -> Option<(String, u8, &[u8])> {
+    let (&len, rest) = frame.split_first()?;
+    let len = len as usize;
+    if rest.len() <= len {
+        return None;
+    }
+    let channel = std::str::from_utf8(&rest[..len]).ok()?.to_string();
+    Some((channel, rest[len], &rest[len + 1..]))
+}
+
+// 客户端发给服务端的文本帧
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientFrame {
+    Hello {
+        version: u32,
+        session_id: Option<String>,
+        #[serde(default)]
+        channels: Vec<ChannelKind>,
+    },
+    Input {
+        channel: String,
+        data: String,
+    },
+    Resize {
+        channel: String,
+        cols: u16,
+        rows: u16,
+    },
+    Close {
+        channel: String,
+    },
+}
+
+enum Outbound {
+    // 日志行，批量发送时以换行拼接
+    Line {
+        channel: String,
+        text: String,
+    },
+    // watch 事件，格式同日志行但不能丢弃，队列满时等待
+    Event {
+        channel: String,
+        text: String,
+    },
+    // 终端文本输出，批量发送时直接拼接
+    Output {
+        channel: String,
+        stream: StdStream,
+        text: String,
+    },
+    // 终端原始字节输出，以二进制帧发送
+    Bytes {
+        channel: String,
+        stream: StdStream,
+        data: Vec<u8>,
+    },
+    // ping 等控制帧，不合并也不丢弃
+    Control(Message),
+}
+
+impl Outbound {
+    fn len(&self) -> usize {
+        match self {
+            Outbound::Line { text, .. }
+            | Outbound::Event { text, .. }
+            | Outbound::Output { text, .. } => text.len(),
+            Outbound::Bytes { data, .. } => data.len(),
+            Outbound::Control(_) => 0,
+        }
+    }
+
+    fn channel(&self) -> Option<&str> {
+        match self {
+            Outbound::Line { channel, .. }
+            | Outbound::Event { channel, .. }
+            | Outbound::Output { channel, .. }
+            | Outbound::Bytes { channel, .. } => Some(channel),
+            Outbound::Control(_) => None,
+        }
+    }
+
+    fn policy(&self) -> OverflowPolicy {
+        match self {
+            Outbound::Line { .. } => OverflowPolicy::DropOldest,
+            _ => OverflowPolicy::Coalesce,
+        }
+    }
+
+    // 尝试把 next 合并进当前消息，成功返回 None
+    fn merge(&mut self, next: Outbound) -> Option<Outbound> {
+        if self.len() + next.len() > MAX_FRAME_BYTES {
+            return Some(next);
+        }
+        match (self, next) {
+            (
+                Outbound::Line { channel, text },
+                Outbound::Line {
+                    channel: next_channel,
+                    text: line,
+                },
+            )
+            | (
+                Outbound::Event { channel, text },
+                Outbound::Event {
+                    channel: next_channel,
+                    text: line,
+                },
+            ) if *channel == next_channel => {
+                text.push('\n');
+                text.push_str(&line);
+                None
+            }
+            (
+                Outbound::Output {
+                    channel,
+                    stream,
+                    text,
+                },
+                Outbound::Output {
+                    channel: next_channel,
+                    stream: next_stream,
+                    text: output,
+                },
+            ) if *channel == next_channel && *stream == next_stream => {
+                text.push_str(&output);
+                None
+            }
+            (
+                Outbound::Bytes {
+                    channel,
+                    stream,
+                    data,
+                },
+                Outbound::Bytes {
+                    channel: next_channel,
+                    stream: next_stream,
+                    data: next_data,
+                },
+            ) if *channel == next_channel && *stream == next_stream => {
+                data.extend_from_slice(&next_data);
+                None
+            }
+            (_, next) => Some(next),
+        }
+    }
+
+    fn into_message(self) -> Message {
+        match self {
+            Outbound::Line { channel, text } | Outbound::Event { channel, text } => {
+                ServerFrame::Data {
+                    channel: &channel,
+                    data: &text,
+                    stream: None,
+                }
+                .to_message()
+            }
+            Outbound::Output {
+                channel,
+                stream,
+                text,
+            } => ServerFrame::Data {
+                channel: &channel,
+                data: &text,
+                stream: (stream != StdStream::Stdout).then_some(stream),
+            }
+            .to_message(),
+            Outbound::Bytes {
+                channel,
+                stream,
+                data,
+            } => encode_binary_frame(&channel, stream, &data),
+            Outbound::Control(msg) => msg,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ClientQueue {
+    items: std::sync::Mutex<VecDeque<Outbound>>,
+    notify: Notify,
+    // 发送任务取走消息或队列关闭时唤醒等待空位的写入方
+    space: Notify,
+    closed: AtomicBool,
+    // 每次有连接接管会话时递增，用于区分新旧连接
+    generation: AtomicU64,
+    // 当前连接发送任务的取消令牌，新连接接管时取消旧的
+    sender: std::sync::Mutex<CancellationToken>,
+    // 发送任务每次取出并发送消息时持有，保证同一时刻只有一个连接在发送
+    sending: Mutex<()>,
+    sent: AtomicU64,
+    frames: AtomicU64,
+    dropped: AtomicU64,
+    coalesced: AtomicU64,
+}
+
+impl ClientQueue {
+    // 入队，Coalesce 的消息既不能合并也没有空位时原样返回，由调用方等待空位
+    fn try_push(&self, item: Outbound) -> Result<Option<Outbound>, String> {
+        if self.closed.load(Ordering::Acquire) {
+            return Err("client closed".to_string());
+        }
+        {
+            let mut items = self.items.lock().unwrap();
+            if items.len() < QUEUE_CAPACITY || matches!(item, Outbound::Control(_)) {
+                items.push_back(item);
+            } else {
+                match item.policy() {
+                    OverflowPolicy::Coalesce => {
+                        let rest = match items.back_mut() {
+                            Some(back) => back.merge(item),
+                            None => Some(item),
+                        };
+                        if rest.is_some() {
+                            return Ok(rest);
+                        }
+                        self.coalesced.fetch_add(1, Ordering::Relaxed);
+                    }
+                    // 只淘汰同一 channel 的日志行，其它流的数据和控制帧不丢；
+                    // 没有可淘汰的行时丢弃新行，队列不超过上限
+                    OverflowPolicy::DropOldest => {
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                        let channel = item.channel();
+                        match items.iter().position(|queued| {
+                            matches!(queued, Outbound::Line { .. }) && queued.channel() == channel
+                        }) {
+                            Some(index) => {
+                                items.remove(index);
+                                items.push_back(item);
+                            }
+                            None => return Ok(None),
+                        }
+                    }
+                }
+            }
+        }
+        self.notify.notify_one();
+        Ok(None)
+    }
+
+    // 队列满时等待发送任务腾出空位，让终端输出的读取方停下来而不是丢数据
+    async fn push(&self, mut item: Outbound) -> Result<(), String> {
+        loop {
+            let space = self.space.notified();
+            tokio::pin!(space);
+            // 先注册再尝试，避免错过两者之间的唤醒
+            space.as_mut().enable();
+            match self.try_push(item)? {
+                None => return Ok(()),
+                Some(rest) => item = rest,
+            }
+            space.await;
+        }
+    }
+
+    // 取出当前排队的全部消息，相邻的同类消息合并为一帧
+    fn drain_batch(&self) -> Vec<Message> {
+        let items: Vec<Outbound> = self.items.lock().unwrap().drain(..).collect();
+        self.sent.fetch_add(items.len() as u64, Ordering::Relaxed);
+
+        let mut batch: Vec<Outbound> = Vec::new();
+        for item in items {
+            let rest = match batch.last_mut() {
+                Some(last) => last.merge(item),
+                None => Some(item),
+            };
+            if let Some(item) = rest {
+                batch.push(item);
+            }
+        }
+        self.frames.fetch_add(batch.len() as u64, Ordering::Relaxed);
+        self.space.notify_waiters();
+        batch.into_iter().map(Outbound::into_message).collect()
+    }
+
+    fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.notify.notify_one();
+        self.space.notify_waiters();
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+
+    // 新连接接管会话：取消旧连接的发送任务，并等待它正在进行的发送结束
+    async fn attach(&self) -> (u64, CancellationToken) {
+        let generation = self.generation.fetch_add(1, Ordering::AcqRel) + 1;
+        let cancel = CancellationToken::new();
+        let previous = std::mem::replace(&mut *self.sender.lock().unwrap(), cancel.clone());
+        previous.cancel();
+        drop(self.sending.lock().await);
+        (generation, cancel)
+    }
+
+    fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    fn stats(&self, client_id: Uuid) -> ClientStats {
+        ClientStats {
+            client_id: client_id.to_string(),
+            queued: self.items.lock().unwrap().len(),
+            sent: self.sent.load(Ordering::Relaxed),
+            frames: self.frames.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            coalesced: self.coalesced.load(Ordering::Relaxed),
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct WebsocketManager {
     clients: Arc<ArcSwap<ClientMap>>,
-    terminal_inputs: Arc<ArcSwap<TerminalInputMap>>,
+    channels: Arc<ArcSwap<ChannelMap>>,
+    // 每次启动随机生成，握手时必须携带
+    token: Arc<String>,
+    port: Arc<AtomicU16>,
 }
 
 impl WebsocketManager {
     pub fn new() -> Self {
         let client_map: ClientMap = Arc::new(Mutex::new(HashMap::new()));
-        let terminal_input_map: TerminalInputMap = Arc::new(Mutex::new(HashMap::new()));
+        let channel_map: ChannelMap = Arc::new(Mutex::new(HashMap::new()));
         Self {
             clients: Arc::new(ArcSwap::new(Arc::new(client_map))),
-            terminal_inputs: Arc::new(ArcSwap::new(Arc::new(terminal_input_map))),
+            channels: Arc::new(ArcSwap::new(Arc::new(channel_map))),
+            token: Arc::new(format!(
+                "{}{}",
+                Uuid::new_v4().simple(),
+                Uuid::new_v4().simple()
+            )),
+            port: Arc::new(AtomicU16::new(0)),
         }
     }
 
-    pub async fn start_server(&self, port: u16) {
-        let listener = TcpListener::bind(format!("127.0.0.1:{}", port))
-            .await
-            .unwrap();
+    // 绑定本地随机端口，端口通过 websocket_info 告知前端
+    pub async fn bind(&self) -> std::io::Result<TcpListener> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let port = listener.local_addr()?.port();
+        self.port.store(port, Ordering::Release);
+        tracing::info!("Websocket server listening on 127.0.0.1:{}", port);
+        Ok(listener)
+    }
+
+    pub async fn start_server(&self, listener: TcpListener) {
         while let Ok((stream, _)) = listener.accept().await {
             let clients = self.clients.clone();
-            let terminal_inputs = self.terminal_inputs.clone();
+            let channels = self.channels.clone();
+            let token = self.token.clone();
             tokio::spawn(async move {
-                Self::handle_connection(clients, terminal_inputs, stream).await;
+                Self::handle_connection(clients, channels, token, stream).await;
             });
         }
     }
 
-    pub async fn handle_connection(
+    pub fn info(&self) -> WebsocketInfo {
+        WebsocketInfo {
+            port: self.port.load(Ordering::Acquire),
+            token: self.token.to_string(),
+        }
+    }
+
+    // 校验握手请求的 token 和 Origin
+    fn authorize(token: &str, request: &Request) -> Result<(), (StatusCode, &'static str)> {
+        if let Some(origin) = request.headers().get("Origin") {
+            let allowed = origin
+                .to_str()
+                .map(|origin| ALLOWED_ORIGINS.contains(&origin))
+                .unwrap_or(false);
+            if !allowed {
+                return Err((StatusCode::FORBIDDEN, "origin not allowed"));
+            }
+        }
+
+        let provided = request
+            .uri()
+            .query()
+            .unwrap_or_default()
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("token="))
+            .unwrap_or_default();
+        // 逐字节比较，避免通过响应时间猜测 token
+        let matched = provided.len() == token.len()
+            && provided
+                .bytes()
+                .zip(token.bytes())
+                .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                == 0;
+        if !matched {
+            return Err((StatusCode::UNAUTHORIZED, "invalid token"));
+        }
+        Ok(())
+    }
+
+    async fn handle_connection(
         clients: Arc<ArcSwap<ClientMap>>,
-        terminal_inputs: Arc<ArcSwap<TerminalInputMap>>,
+        channels: Arc<ArcSwap<ChannelMap>>,
+        token: Arc<String>,
         stream: TcpStream,
     ) {
-        let ws_stream = match tokio_tungstenite::accept_async(stream).await {
+        #[allow(clippy::result_large_err)]
+        let callback = |request: &Request, response: Response| {
+            Self::authorize(&token, request)
+                .map(|_| response)
+                .map_err(|(status, reason)| {
+                    let mut response = ErrorResponse::new(Some(reason.to_string()));
+                    *response.status_mut() = status;
+                    response
+                })
+        };
+        let ws_stream = match tokio_tungstenite::accept_hdr_async(stream, callback).await {
             Ok(ws) => ws,
             Err(e) => {
                 eprintln!("WebSocket handshake failed: {}", e);
@@ -56,33 +553,81 @@ impl WebsocketManager {
         };
 
         let (mut ws_sender, mut ws_receiver) = ws_stream.split();
-        let client_id = Uuid::new_v4();
-        let (tx, mut rx) = mpsc::unbounded_channel();
 
-        let loaded_clients = clients.load();
-        {
-            let mut guard = loaded_clients.lock().await;
-            guard.insert(client_id, tx.clone());
+        let (version, session_id, requested) =
+            match tokio::time::timeout(HELLO_TIMEOUT, Self::read_hello(&mut ws_receiver)).await {
+                Ok(Some(hello)) => hello,
+                _ => {
+                    let frame = ServerFrame::Error {
+                        message: "expected hello",
+                    };
+                    let _ = ws_sender.send(frame.to_message()).await;
+                    let _ = ws_sender.close().await;
+                    return;
+                }
+            };
+        if version != PROTOCOL_VERSION {
+            let message = format!("unsupported protocol version {}", version);
+            let frame = ServerFrame::Error { message: &message };
+            let _ = ws_sender.send(frame.to_message()).await;
+            let _ = ws_sender.close().await;
+            return;
         }
 
-        tokio::time::sleep(Duration::from_millis(1000)).await;
-        let client_id_str = client_id.to_string();
-        println!("Sending client ID to client: {}", client_id_str);
-        if let Err(e) = tx.send(Message::Text(client_id_str.into())) {
-            eprintln!("Failed to send client ID to client {}: {}", client_id, e);
+        // 会话仍在保留期内则恢复，否则新建
+        let loaded_clients = clients.load();
+        let (client_id, queue, resumed) = {
             let mut guard = loaded_clients.lock().await;
-            guard.remove(&client_id);
+            let existing = session_id
+                .and_then(|id| Uuid::parse_str(&id).ok())
+                .and_then(|id| guard.get(&id).map(|queue| (id, queue.clone())))
+                .filter(|(_, queue)| !queue.is_closed());
+            match existing {
+                Some((client_id, queue)) => (client_id, queue, true),
+                None => {
+                    let client_id = Uuid::new_v4();
+                    let queue = Arc::new(ClientQueue::default());
+                    guard.insert(client_id, queue.clone());
+                    (client_id, queue, false)
+                }
+            }
+        };
+        let (generation, send_cancel) = queue.attach().await;
+
+        let supported = [
+            ChannelKind::Log,
+            ChannelKind::Exec,
+            ChannelKind::Watch,
+            ChannelKind::Replay,
+        ];
+        let capabilities = supported
+            .into_iter()
+            .filter(|kind| requested.is_empty() || requested.contains(kind))
+            .collect();
+        let welcome = ServerFrame::Welcome {
+            session_id: client_id.to_string(),
+            version: PROTOCOL_VERSION,
+            resumed,
+            capabilities,
+        };
+        if let Err(e) = ws_sender.send(welcome.to_message()).await {
+            eprintln!("Failed to send welcome to client {}: {}", client_id, e);
+            Self::expire_session(clients, channels, client_id, queue, generation);
             return;
         }
+        println!("Client {} connected, resumed: {}", client_id, resumed);
 
         let mut ping_interval = tokio::time::interval(tokio::time::Duration::from_secs(5));
-        let ping_tx = tx.clone();
+        let ping_queue = queue.clone();
         let mut ping_failures = 0;
 
-        let heartbeat_task = tokio::spawn(async move {
+        let mut heartbeat_task = tokio::spawn(async move {
             loop {
                 ping_interval.tick().await;
-                if let Err(_) = ping_tx.send(Message::Ping("ping".into())) {
+                if ping_queue
+                    .try_push(Outbound::Control(Message::Ping("ping".into())))
+                    .is_err()
+                {
                     ping_failures += 1;
                     if ping_failures >= 3 {
                         break;
@@ -93,20 +638,38 @@ impl WebsocketManager {
             }
         });
 
-        let send_task = tokio::spawn(async move {
-            while let Some(msg) = rx.recv().await {
-                match ws_sender.send(msg).await {
-                    Ok(_) => {}
-                    Err(e) => {
+        let send_queue = queue.clone();
+        let mut send_task = tokio::spawn(async move {
+            let mut flush_interval = tokio::time::interval(FLUSH_INTERVAL);
+            flush_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            // 恢复的会话可能已有排队消息
+            send_queue.notify.notify_one();
+            loop {
+                tokio::select! {
+                    _ = send_cancel.cancelled() => break,
+                    _ = send_queue.notify.notified() => {}
+                }
+                if send_queue.is_closed() {
+                    break;
+                }
+                // 限制发送频率，高频日志在一个 tick 内合并成一帧
+                flush_interval.tick().await;
+                let _sending = send_queue.sending.lock().await;
+                // 拿到发送锁后再确认没有被新连接接管，旧连接不会再取走消息
+                if send_cancel.is_cancelled() {
+                    break;
+                }
+                for msg in send_queue.drain_batch() {
+                    if let Err(e) = ws_sender.send(msg).await {
                         eprintln!("Send error to client {}: {}", client_id, e);
-                        break;
+                        return;
                     }
                 }
             }
         });
 
-        let terminal_inputs_recv = terminal_inputs.clone();
-        let recv_task = tokio::spawn(async move {
+        let channels_recv = channels.clone();
+        let mut recv_task = tokio::spawn(async move {
             while let Some(result) = ws_receiver.next().await {
                 match result {
                     Ok(msg) => match msg {
@@ -115,31 +678,14 @@ impl WebsocketManager {
                         }
                         Message::Close(_) => {
                             println!("Client {} closed connection gracefully", client_id);
-                            break;
+                            return true;
                         }
                         Message::Text(text) => {
-                            // 如果是终端输入，转发给对应的终端会话
-                            if let Ok(terminal_input_map) = terminal_inputs_recv.load().try_lock() {
-                                if let Some(sender) = terminal_input_map.get(&client_id) {
-                                    if let Err(e) = sender.send(text.to_string()) {
-                                        eprintln!("Failed to send terminal input: {}", e);
-                                    }
-                                }
-                            }
+                            Self::handle_client_frame(&channels_recv, client_id, text.as_bytes())
+                                .await;
                         }
                         Message::Binary(data) => {
-                            // 处理二进制数据（可能是终端输入）
-                            if let Ok(text) = String::from_utf8(data.to_vec()) {
-                                if let Ok(terminal_input_map) =
-                                    terminal_inputs_recv.load().try_lock()
-                                {
-                                    if let Some(sender) = terminal_input_map.get(&client_id) {
-                                        if let Err(e) = sender.send(text) {
-                                            eprintln!("Failed to send terminal input: {}", e);
-                                        }
-                                    }
-                                }
-                            }
+                            Self::handle_binary_frame(&channels_recv, client_id, &data).await;
                         }
                         _ => {
                             println!("Received other message from {}: {:?}", client_id, msg);
@@ -157,51 +703,324 @@ impl WebsocketManager {
                     }
                 }
             }
+            false
         });
 
-        tokio::select! {
-            _ = send_task => {
+        let graceful = tokio::select! {
+            _ = &mut send_task => {
                 println!("Send task ended for client {}", client_id);
+                false
             },
-            _ = recv_task => {
+            result = &mut recv_task => {
                 println!("Receive task ended for client {}", client_id);
+                result.unwrap_or(false)
             },
-            _ = heartbeat_task => {
+            _ = &mut heartbeat_task => {
                 println!("Heartbeat task ended for client {}", client_id);
+                false
             },
+        };
+        send_task.abort();
+        recv_task.abort();
+        heartbeat_task.abort();
+
+        if graceful {
+            // 客户端主动关闭，不再保留会话
+            Self::expire_session(clients, channels, client_id, queue, generation);
+            return;
+        }
+
+        println!(
+            "Connection for client {} lost, keeping session for {:?}",
+            client_id, SESSION_GRACE
+        );
+        tokio::spawn(async move {
+            tokio::time::sleep(SESSION_GRACE).await;
+            Self::expire_session(clients, channels, client_id, queue, generation);
+        });
+    }
+
+    async fn read_hello(
+        ws_receiver: &mut SplitStream<WebSocketStream<TcpStream>>,
+    ) -> Option<(u32, Option<String>, Vec<ChannelKind>)> {
+        while let Some(Ok(msg)) = ws_receiver.next().await {
+            let data = match msg {
+                Message::Text(text) => text.as_bytes().to_vec(),
+                Message::Binary(data) => data.to_vec(),
+                Message::Close(_) => return None,
+                _ => continue,
+            };
+            return match serde_json::from_slice::<ClientFrame>(&data) {
+                Ok(ClientFrame::Hello {
+                    version,
+                    session_id,
+                    channels,
+                }) => Some((version, session_id, channels)),
+                _ => None,
+            };
+        }
+        None
+    }
+
+    // 会话没有被新连接接管时，关闭队列并取消其上的所有流
+    fn expire_session(
+        clients: Arc<ArcSwap<ClientMap>>,
+        channels: Arc<ArcSwap<ChannelMap>>,
+        client_id: Uuid,
+        queue: Arc<ClientQueue>,
+        generation: u64,
+    ) {
+        tokio::spawn(async move {
+            if queue.generation() != generation {
+                return;
+            }
+            println!("Cleaning up session for client {}", client_id);
+            queue.close();
+            clients.load().lock().await.remove(&client_id);
+
+            let channels_guard = channels.load();
+            let mut channel_guard = channels_guard.lock().await;
+            channel_guard.retain(|(id, _), channel| {
+                if *id == client_id {
+                    channel.cancel.cancel();
+                    false
+                } else {
+                    true
+                }
+            });
+        });
+    }
+
+    fn forward_input(
+        channel_map: &HashMap<(Uuid, String), Channel>,
+        client_id: Uuid,
+        channel: String,
+        input: ChannelInput,
+    ) {
+        if let Some(sender) = channel_map
+            .get(&(client_id, channel))
+            .and_then(|channel| channel.input.as_ref())
+        {
+            if let Err(e) = sender.send(input) {
+                eprintln!("Failed to send terminal input: {}", e);
+            }
+        }
+    }
+
+    // 二进制帧只用于 stdin 原始字节
+    async fn handle_binary_frame(channels: &ArcSwap<ChannelMap>, client_id: Uuid, data: &[u8]) {
+        match decode_binary_frame(data) {
+            Some((channel, stream, payload)) if stream == StdStream::Stdin as u8 => {
+                let guard = channels.load();
+                let channel_map = guard.lock().await;
+                let input = ChannelInput::Data(payload.to_vec());
+                Self::forward_input(&channel_map, client_id, channel, input);
+            }
+            _ => eprintln!("Invalid binary frame from client {}", client_id),
+        }
+    }
+
+    async fn handle_client_frame(channels: &ArcSwap<ChannelMap>, client_id: Uuid, data: &[u8]) {
+        let frame = match serde_json::from_slice::<ClientFrame>(data) {
+            Ok(frame) => frame,
+            Err(e) => {
+                eprintln!("Invalid frame from client {}: {}", client_id, e);
+                return;
+            }
+        };
+        let guard = channels.load();
+        let channel_map = guard.lock().await;
+        match frame {
+            ClientFrame::Input { channel, data } => {
+                // 终端输入转发给对应 channel 的会话
+                let input = ChannelInput::Data(data.into_bytes());
+                Self::forward_input(&channel_map, client_id, channel, input);
+            }
+            ClientFrame::Resize {
+                channel,
+                cols,
+                rows,
+            } => {
+                Self::forward_input(
+                    &channel_map,
+                    client_id,
+                    channel,
+                    ChannelInput::Resize { cols, rows },
+                );
+            }
+            ClientFrame::Close { channel } => {
+                if let Some(channel) = channel_map.get(&(client_id, channel)) {
+                    channel.cancel.cancel();
+                }
+            }
+            ClientFrame::Hello { .. } => {
+                eprintln!("Unexpected hello from client {}", client_id);
+            }
         }
+    }
 
-        println!("Cleaning up connection for client {}", client_id);
-        let mut guard = loaded_clients.lock().await;
-        guard.remove(&client_id);
+    async fn enqueue(&self, client_id: Uuid, item: Outbound) -> Result<(), String> {
+        // 等待空位时不能持有客户端表的锁
+        let queue = {
+            let guard = self.clients.load();
+            let clients = guard.lock().await;
+            clients.get(&client_id).cloned()
+        };
+        match queue {
+            Some(queue) => queue.push(item).await,
+            None => Err("client not found".to_string()),
+        }
+    }
 
-        // 清理终端输入通道
-        let terminal_inputs_guard = terminal_inputs.load();
-        let mut terminal_guard = terminal_inputs_guard.lock().await;
-        terminal_guard.remove(&client_id);
+    // 发送终端文本输出，队列满时合并到队尾或等待空位
+    pub async fn send_text(
+        &self,
+        client_id: Uuid,
+        channel: &str,
+        stream: StdStream,
+        text: String,
+    ) -> Result<(), String> {
+        let item = Outbound::Output {
+            channel: channel.to_string(),
+            stream,
+            text,
+        };
+        self.enqueue(client_id, item).await
     }
 
-    pub async fn send_message(&self, client_id: Uuid, message: String) -> Result<(), String> {
+    // 发送终端原始字节，客户端收到二进制帧
+    pub async fn send_bytes(
+        &self,
+        client_id: Uuid,
+        channel: &str,
+        stream: StdStream,
+        data: Vec<u8>,
+    ) -> Result<(), String> {
+        let item = Outbound::Bytes {
+            channel: channel.to_string(),
+            stream,
+            data,
+        };
+        self.enqueue(client_id, item).await
+    }
+
+    pub async fn send_status(
+        &self,
+        client_id: Uuid,
+        channel: &str,
+        status: serde_json::Value,
+    ) -> Result<(), String> {
+        let frame = ServerFrame::Status {
+            channel,
+            status: &status,
+        };
+        self.enqueue(client_id, Outbound::Control(frame.to_message()))
+            .await
+    }
+
+    // 发送一行日志，队列满时丢弃最旧的行
+    pub async fn send_line(
+        &self,
+        client_id: Uuid,
+        channel: &str,
+        line: String,
+    ) -> Result<(), String> {
+        let item = Outbound::Line {
+            channel: channel.to_string(),
+            text: line,
+        };
+        self.enqueue(client_id, item).await
+    }
+
+    // 发送一条 watch 事件，队列满时等待而不是丢弃
+    pub async fn send_event(
+        &self,
+        client_id: Uuid,
+        channel: &str,
+        event: String,
+    ) -> Result<(), String> {
+        let item = Outbound::Event {
+            channel: channel.to_string(),
+            text: event,
+        };
+        self.enqueue(client_id, item).await
+    }
+
+    pub async fn stats(&self) -> Vec<ClientStats> {
         let guard = self.clients.load();
         let clients = guard.lock().await;
-        if let Some(sender) = clients.get(&client_id) {
-            sender
-                .send(Message::Text(message.into()))
-                .map_err(|e| e.to_string())
-        } else {
-            Err("client not found".to_string())
-        }
+        clients
+            .iter()
+            .map(|(client_id, queue)| queue.stats(*client_id))
+            .collect()
     }
 
-    pub async fn set_terminal_input(&self, client_id: Uuid, sender: TerminalInputSender) {
-        let guard = self.terminal_inputs.load();
-        let mut terminal_inputs = guard.lock().await;
-        terminal_inputs.insert(client_id, sender);
+    pub async fn channels(&self) -> Vec<ChannelInfo> {
+        let guard = self.channels.load();
+        let channels = guard.lock().await;
+        channels
+            .iter()
+            .map(|((client_id, channel_id), channel)| ChannelInfo {
+                client_id: client_id.to_string(),
+                channel_id: channel_id.clone(),
+                kind: channel.kind,
+                interactive: channel.input.is_some(),
+            })
+            .collect()
     }
 
-    pub async fn remove_terminal_input(&self, client_id: Uuid) {
-        let guard = self.terminal_inputs.load();
-        let mut terminal_inputs = guard.lock().await;
-        terminal_inputs.remove(&client_id);
+    // 在连接上注册一个流，客户端关闭 channel 或断开连接时返回的 token 会被取消
+    pub async fn open_channel(
+        &self,
+        client_id: Uuid,
+        channel_id: &str,
+        kind: ChannelKind,
+        input: Option<ChannelInputSender>,
+    ) -> Result<CancellationToken, String> {
+        {
+            let guard = self.clients.load();
+            if !guard.lock().await.contains_key(&client_id) {
+                return Err("client not found".to_string());
+            }
+        }
+        let guard = self.channels.load();
+        let mut channels = guard.lock().await;
+        // channel 长度需要放进二进制帧的一个字节里
+        if channel_id.is_empty() || channel_id.len() > u8::MAX as usize {
+            return Err(format!("invalid channel id {}", channel_id));
+        }
+        let key = (client_id, channel_id.to_string());
+        if channels.contains_key(&key) {
+            return Err(format!("channel {} already in use", channel_id));
+        }
+        let cancel = CancellationToken::new();
+        channels.insert(
+            key,
+            Channel {
+                kind,
+                input,
+                cancel: cancel.clone(),
+            },
+        );
+        Ok(cancel)
+    }
+
+    // 流结束时移除 channel 并通知客户端
+    pub async fn close_channel(&self, client_id: Uuid, channel_id: &str) {
+        let removed = {
+            let guard = self.channels.load();
+            let mut channels = guard.lock().await;
+            channels.remove(&(client_id, channel_id.to_string()))
+        };
+        if let Some(channel) = removed {
+            channel.cancel.cancel();
+            let frame = ServerFrame::Closed {
+                channel: channel_id,
+            };
+            // 连接可能已经断开，忽略发送失败
+            let _ = self
+                .enqueue(client_id, Outbound::Control(frame.to_message()))
+                .await;
+        }
     }
 }
@@ -26,6 +26,18 @@ pub enum MyError {
     InvalidMethod(String),
     #[error("InvalidUuid: {0}")]
     InvalidUuid(String),
+    #[error("UnsupportedKind: {0}")]
+    UnsupportedKind(String),
+    #[error("WebsocketError: {0}")]
+    WebsocketError(String),
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+    #[error("NoShell: {0}")]
+    NoShell(String),
+    #[error("MissingCommand: {0}")]
+    MissingCommand(String),
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
 }
 
 impl From<kube::Error> for MyError {
@@ -68,3 +80,9 @@ impl From<http::Error> for MyError {
         MyError::HttpError(value.to_string())
     }
 }
+
+impl From<zip::result::ZipError> for MyError {
+    fn from(value: zip::result::ZipError) -> Self {
+        MyError::IOError(value.to_string())
+    }
+}
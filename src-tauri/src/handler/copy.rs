@@ -0,0 +1,331 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use k8s_openapi::{api::core::v1::Pod, apimachinery::pkg::apis::meta::v1::Status};
+use kube::api::{Api, AttachParams};
+use tauri::{AppHandle, Emitter};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    select,
+};
+use tokio_util::{io::SyncIoBridge, sync::CancellationToken};
+
+use crate::{
+    error::MyError,
+    handler::terminal,
+    resource::copy::{FileCopyProgress, FileCopyResult},
+};
+
+const PROGRESS_EVENT: &str = "file_copy_progress";
+const PROGRESS_INTERVAL: u64 = 256 * 1024;
+// 本地 tar 线程和 exec 流之间的缓冲
+const PIPE_CAPACITY: usize = 64 * 1024;
+
+// 一次复制涉及的容器
+pub struct CopyTarget<'a> {
+    pub pods: &'a Api<Pod>,
+    pub pod: &'a str,
+    pub container: &'a str,
+}
+
+/// 根据 exec 的退出状态判断命令是否失败，可执行文件不存在时返回 MissingCommand
+pub fn exec_failure(
+    status: Option<Status>,
+    stderr: &str,
+    program: &str,
+    container: &str,
+) -> Option<MyError> {
+    let status = status?;
+    if status.status.as_deref() == Some("Success") {
+        return None;
+    }
+    let message = status.message.unwrap_or_default();
+    let exit_code = status
+        .details
+        .and_then(|details| details.causes)
+        .into_iter()
+        .flatten()
+        .find(|cause| cause.reason.as_deref() == Some("ExitCode"))
+        .and_then(|cause| cause.message);
+    let missing = message.contains("executable file not found")
+        || message.contains("no such file or directory")
+        || exit_code.as_deref() == Some("127");
+    if missing {
+        return Some(MyError::MissingCommand(format!(
+            "{} is not available in container {}",
+            program, container
+        )));
+    }
+    let stderr = stderr.trim();
+    Some(MyError::KubeError(if stderr.is_empty() {
+        message
+    } else {
+        stderr.to_string()
+    }))
+}
+
+pub async fn read_to_string<R: AsyncRead + Unpin>(reader: Option<R>) -> String {
+    let mut output = Vec::new();
+    if let Some(mut reader) = reader {
+        if let Err(e) = reader.read_to_end(&mut output).await {
+            tracing::warn!("Failed to read exec output: {}", e);
+        }
+    }
+    String::from_utf8_lossy(&output).into_owned()
+}
+
+// 在两个流之间复制并计数，取消时立即返回
+async fn pump<R, W>(
+    mut reader: R,
+    mut writer: W,
+    cancel: &CancellationToken,
+    mut on_progress: impl FnMut(u64),
+) -> Result<u64, MyError>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut buffer = [0u8; 8192];
+    let mut copied = 0u64;
+    let mut reported = 0u64;
+    loop {
+        let n = select! {
+            n = reader.read(&mut buffer) => n?,
+            _ = cancel.cancelled() => {
+                return Err(MyError::Cancelled("File copy cancelled".to_string()));
+            }
+        };
+        if n == 0 {
+            break;
+        }
+        writer.write_all(&buffer[..n]).await?;
+        copied += n as u64;
+        if copied - reported >= PROGRESS_INTERVAL {
+            reported = copied;
+            on_progress(copied);
+        }
+    }
+    writer.shutdown().await?;
+    Ok(copied)
+}
+
+fn emit_progress(
+    app: &AppHandle,
+    id: &str,
+    bytes: u64,
+    total: Option<u64>,
+    finished: bool,
+    error: Option<String>,
+) {
+    let progress = FileCopyProgress {
+        id: id.to_string(),
+        bytes,
+        total,
+        finished,
+        error,
+    };
+    if let Err(e) = app.emit(PROGRESS_EVENT, progress) {
+        tracing::warn!("Failed to emit file copy progress: {}", e);
+    }
+}
+
+// 容器内路径拆成 tar -C 的目录和条目名
+fn split_remote_path(path: &str) -> Result<(&str, &str), MyError> {
+    let path = path.trim_end_matches('/');
+    let (parent, name) = match path.rsplit_once('/') {
+        Some(("", name)) => ("/", name),
+        Some((parent, name)) => (parent, name),
+        None => (".", path),
+    };
+    if name.is_empty() || name == "." || name == ".." {
+        return Err(MyError::OtherError(format!(
+            "Invalid remote path: {}",
+            path
+        )));
+    }
+    Ok((parent, name))
+}
+
+fn local_size(path: &Path) -> std::io::Result<u64> {
+    let metadata = std::fs::metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+    let mut size = 0;
+    for entry in std::fs::read_dir(path)? {
+        size += local_size(&entry?.path())?;
+    }
+    Ok(size)
+}
+
+/// 在容器内执行 `tar cf -`，将输出解包到本地目录
+pub async fn copy_from_pod(
+    app: &AppHandle,
+    target: CopyTarget<'_>,
+    id: &str,
+    remote_path: &str,
+    local_dir: &Path,
+    cancel: &CancellationToken,
+) -> Result<FileCopyResult, MyError> {
+    let (parent, name) = split_remote_path(remote_path)?;
+    std::fs::create_dir_all(local_dir)?;
+
+    let params = AttachParams::default()
+        .container(target.container)
+        .stdin(false)
+        .stdout(true)
+        .stderr(true);
+    let mut attached = target
+        .pods
+        .exec(target.pod, ["tar", "cf", "-", "-C", parent, name], &params)
+        .await
+        .map_err(terminal::exec_error)?;
+    let stdout = attached
+        .stdout()
+        .ok_or_else(|| MyError::KubeError("No stdout available".to_string()))?;
+    let stderr_task = tokio::spawn(read_to_string(attached.stderr()));
+    let status = attached.take_status();
+
+    let (pipe_writer, pipe_reader) = tokio::io::duplex(PIPE_CAPACITY);
+    let reader = SyncIoBridge::new(pipe_reader);
+    let dest = local_dir.to_path_buf();
+    // tar 解包会拒绝包含 .. 的条目，不会写到目标目录之外
+    let unpack = tokio::task::spawn_blocking(move || -> std::io::Result<()> {
+        let mut archive = tar::Archive::new(reader);
+        archive.unpack(&dest)?;
+        // 结束标记后面还有补齐的记录，读完以免 pump 写入时遇到 BrokenPipe
+        std::io::copy(&mut archive.into_inner(), &mut std::io::sink())?;
+        Ok(())
+    });
+
+    let copied = pump(stdout, pipe_writer, cancel, |bytes| {
+        emit_progress(app, id, bytes, None, false, None)
+    })
+    .await;
+    if let Err(e @ MyError::Cancelled(_)) = copied {
+        attached.abort();
+        emit_progress(app, id, 0, None, true, Some(e.to_string()));
+        return Err(e);
+    }
+    let unpacked = unpack
+        .await
+        .map_err(|e| MyError::OtherError(e.to_string()))?;
+    let status = match status {
+        Some(status) => status.await,
+        None => None,
+    };
+    let stderr = stderr_task.await.unwrap_or_default();
+
+    // 容器内没有 tar 时流会提前结束，优先报告 exec 的失败原因
+    let result = match exec_failure(status, &stderr, "tar", target.container) {
+        Some(e) => Err(e),
+        None => copied.and_then(|bytes| unpacked.map(|_| bytes).map_err(MyError::from)),
+    };
+    match result {
+        Ok(bytes) => {
+            emit_progress(app, id, bytes, None, true, None);
+            Ok(FileCopyResult {
+                id: id.to_string(),
+                path: local_dir.join(name).to_string_lossy().to_string(),
+                bytes,
+            })
+        }
+        Err(e) => {
+            emit_progress(app, id, 0, None, true, Some(e.to_string()));
+            Err(e)
+        }
+    }
+}
+
+/// 本地打包后通过 stdin 交给容器内的 `tar xf -` 解包
+pub async fn copy_to_pod(
+    app: &AppHandle,
+    target: CopyTarget<'_>,
+    id: &str,
+    local_path: &Path,
+    remote_dir: &str,
+    cancel: &CancellationToken,
+) -> Result<FileCopyResult, MyError> {
+    let name = local_path
+        .file_name()
+        .ok_or_else(|| {
+            MyError::OtherError(format!("Invalid local path: {}", local_path.display()))
+        })?
+        .to_os_string();
+    let total = local_size(local_path)?;
+
+    let params = AttachParams::default()
+        .container(target.container)
+        .stdin(true)
+        .stdout(false)
+        .stderr(true);
+    let mut attached = target
+        .pods
+        .exec(target.pod, ["tar", "xf", "-", "-C", remote_dir], &params)
+        .await
+        .map_err(terminal::exec_error)?;
+    let stdin = attached
+        .stdin()
+        .ok_or_else(|| MyError::KubeError("No stdin available".to_string()))?;
+    let stderr_task = tokio::spawn(read_to_string(attached.stderr()));
+    let status = attached.take_status();
+
+    let (pipe_writer, pipe_reader) = tokio::io::duplex(PIPE_CAPACITY);
+    let writer = SyncIoBridge::new(pipe_writer);
+    let source: PathBuf = local_path.to_path_buf();
+    let build = tokio::task::spawn_blocking(move || -> std::io::Result<()> {
+        let mut builder = tar::Builder::new(writer);
+        if source.is_dir() {
+            builder.append_dir_all(&name, &source)?;
+        } else {
+            builder.append_path_with_name(&source, &name)?;
+        }
+        let mut writer = builder.into_inner()?;
+        writer.flush()?;
+        writer.shutdown()
+    });
+
+    // tar 头和补齐块会让传输字节数略大于文件总大小
+    let copied = pump(pipe_reader, stdin, cancel, |bytes| {
+        emit_progress(app, id, bytes.min(total), Some(total), false, None)
+    })
+    .await;
+    if let Err(e @ MyError::Cancelled(_)) = copied {
+        attached.abort();
+        emit_progress(app, id, 0, Some(total), true, Some(e.to_string()));
+        return Err(e);
+    }
+    let built = build
+        .await
+        .map_err(|e| MyError::OtherError(e.to_string()))?;
+    let status = match status {
+        Some(status) => status.await,
+        None => None,
+    };
+    let stderr = stderr_task.await.unwrap_or_default();
+
+    let result = match exec_failure(status, &stderr, "tar", target.container) {
+        Some(e) => Err(e),
+        None => copied.and_then(|bytes| built.map(|_| bytes).map_err(MyError::from)),
+    };
+    match result {
+        Ok(bytes) => {
+            emit_progress(app, id, total, Some(total), true, None);
+            Ok(FileCopyResult {
+                id: id.to_string(),
+                path: format!(
+                    "{}/{}",
+                    remote_dir.trim_end_matches('/'),
+                    local_path.file_name().unwrap_or_default().to_string_lossy()
+                ),
+                bytes,
+            })
+        }
+        Err(e) => {
+            emit_progress(app, id, 0, Some(total), true, Some(e.to_string()));
+            Err(e)
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use futures::future::join_all;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+use kube::{
+    api::{Api, DeleteParams, DynamicObject, ListParams, PropagationPolicy},
+    discovery::{ApiCapabilities, ApiResource, Scope},
+    runtime::wait::{await_condition, conditions::is_deleted},
+    Client, ResourceExt,
+};
+
+use crate::{
+    error::MyError,
+    resource::delete::{DeleteResult, DependentNode},
+    utils::discovery::dynamic_api,
+};
+
+// 依赖对象及其 ownerReference 中的 blockOwnerDeletion
+struct Dependent {
+    resource: ApiResource,
+    meta: ObjectMeta,
+    block_owner_deletion: bool,
+}
+
+pub fn parse_propagation_policy(policy: &str) -> Result<PropagationPolicy, MyError> {
+    match policy.to_lowercase().as_str() {
+        "foreground" => Ok(PropagationPolicy::Foreground),
+        "background" => Ok(PropagationPolicy::Background),
+        "orphan" => Ok(PropagationPolicy::Orphan),
+        _ => Err(MyError::OtherError(format!(
+            "Invalid propagation policy: {}",
+            policy
+        ))),
+    }
+}
+
+// 列出可能引用 owner 的所有对象，按 owner uid 建立索引
+async fn dependents_index(
+    client: Client,
+    resources: &[(ApiResource, ApiCapabilities)],
+    namespace: Option<&str>,
+) -> HashMap<String, Vec<Dependent>> {
+    // 命名空间对象只能被同一命名空间或集群级的对象拥有
+    let lists = resources
+        .iter()
+        .filter(|(_, capabilities)| namespace.is_none() || capabilities.scope == Scope::Namespaced)
+        .map(|(resource, capabilities)| {
+            let api = dynamic_api(client.clone(), resource, capabilities, namespace);
+            async move { (resource, api.list_metadata(&ListParams::default()).await) }
+        });
+
+    let mut seen = HashSet::new();
+    let mut index: HashMap<String, Vec<Dependent>> = HashMap::new();
+    for (resource, list) in join_all(lists).await {
+        let list = match list {
+            Ok(list) => list,
+            Err(e) => {
+                tracing::debug!(
+                    "Skipping {} when collecting dependents: {}",
+                    resource.plural,
+                    e
+                );
+                continue;
+            }
+        };
+        for object in list.items {
+            // 同一资源可能在多个 API 组中出现
+            if !seen.insert(object.metadata.uid.clone()) {
+                continue;
+            }
+            for owner in object.metadata.owner_references.iter().flatten() {
+                index.entry(owner.uid.clone()).or_default().push(Dependent {
+                    resource: resource.clone(),
+                    meta: object.metadata.clone(),
+                    block_owner_deletion: owner.block_owner_deletion.unwrap_or(false),
+                });
+            }
+        }
+    }
+    index
+}
+
+fn build_children(
+    index: &HashMap<String, Vec<Dependent>>,
+    uid: &str,
+    visited: &mut HashSet<String>,
+) -> Vec<DependentNode> {
+    let Some(dependents) = index.get(uid) else {
+        return Vec::new();
+    };
+    let mut children = Vec::new();
+    for dependent in dependents {
+        let uid = dependent.meta.uid.clone().unwrap_or_default();
+        // ownerReferences 理论上无环，防御异常数据
+        if !visited.insert(uid.clone()) {
+            continue;
+        }
+        children.push(DependentNode {
+            api_version: dependent.resource.api_version.clone(),
+            kind: dependent.resource.kind.clone(),
+            namespace: dependent.meta.namespace.clone(),
+            name: dependent.meta.name.clone().unwrap_or_default(),
+            children: build_children(index, &uid, visited),
+            uid,
+            block_owner_deletion: dependent.block_owner_deletion,
+        });
+    }
+    children
+}
+
+/// 根据 ownerReferences 计算删除对象时会被垃圾回收的依赖树
+pub async fn dependents(
+    client: Client,
+    resources: &[(ApiResource, ApiCapabilities)],
+    resource: &ApiResource,
+    capabilities: &ApiCapabilities,
+    namespace: Option<&str>,
+    name: &str,
+) -> Result<DependentNode, MyError> {
+    let root = dynamic_api(client.clone(), resource, capabilities, namespace)
+        .get_metadata(name)
+        .await?;
+    let uid = root.uid().unwrap_or_default();
+    let namespace = match capabilities.scope {
+        Scope::Namespaced => namespace,
+        Scope::Cluster => None,
+    };
+    let index = dependents_index(client, resources, namespace).await;
+    let mut visited = HashSet::from([uid.clone()]);
+    Ok(DependentNode {
+        api_version: resource.api_version.clone(),
+        kind: resource.kind.clone(),
+        namespace: root.namespace(),
+        name: name.to_string(),
+        children: build_children(&index, &uid, &mut visited),
+        uid,
+        block_owner_deletion: false,
+    })
+}
+
+/// 按指定的传播策略删除对象，timeout 不为空时等待对象从 API 中消失
+pub async fn delete(
+    client: Client,
+    resource: &ApiResource,
+    capabilities: &ApiCapabilities,
+    namespace: Option<&str>,
+    name: &str,
+    params: DeleteParams,
+    timeout: Option<Duration>,
+) -> Result<DeleteResult, MyError> {
+    let api: Api<DynamicObject> = dynamic_api(client, resource, capabilities, namespace);
+    let mut result = DeleteResult {
+        kind: resource.kind.clone(),
+        namespace: match capabilities.scope {
+            Scope::Namespaced => namespace.map(|namespace| namespace.to_string()),
+            Scope::Cluster => None,
+        },
+        name: name.to_string(),
+        uid: None,
+        deleted: false,
+    };
+    match api.delete(name, &params).await? {
+        // 返回对象说明删除还在进行（finalizer 或前台删除）
+        either::Either::Left(object) => {
+            let uid = object.uid().unwrap_or_default();
+            result.uid = Some(uid.clone());
+            if let Some(timeout) = timeout {
+                let condition = await_condition(api, name, is_deleted(&uid));
+                match tokio::time::timeout(timeout, condition).await {
+                    Ok(waited) => {
+                        waited.map_err(|e| MyError::WatchError(e.to_string()))?;
+                        result.deleted = true;
+                    }
+                    Err(_) => tracing::info!("Timed out waiting for {} to be deleted", name),
+                }
+            }
+        }
+        either::Either::Right(_) => result.deleted = true,
+    }
+    Ok(result)
+}
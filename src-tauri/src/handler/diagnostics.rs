@@ -0,0 +1,734 @@
+use k8s_openapi::api::core::v1::{
+    ContainerStatus, Event, Node, PersistentVolumeClaim, Pod, PodCondition,
+};
+use kube::{
+    api::{Api, ListParams},
+    Client, ResourceExt,
+};
+
+use crate::{
+    error::MyError,
+    resource::diagnostics::{Finding, PodDiagnostics, Severity},
+};
+
+// 重启次数达到该值时提示
+const RESTART_WARNING: i32 = 5;
+
+// 规则检查需要的全部数据，一次性读取
+pub struct DiagnosticContext {
+    pub pod: Pod,
+    pub events: Vec<Event>,
+    pub node: Option<Node>,
+    // PVC 名称及其对象，不存在时为空
+    pub claims: Vec<(String, Option<PersistentVolumeClaim>)>,
+}
+
+type Rule = fn(&DiagnosticContext) -> Vec<Finding>;
+
+const RULES: &[Rule] = &[
+    scheduling,
+    image_pull,
+    container_config,
+    crash_loop,
+    oom_killed,
+    probe_failures,
+    volume_claims,
+    node_conditions,
+    evicted,
+    unready,
+];
+
+fn finding(
+    rule: &str,
+    severity: Severity,
+    container: Option<&str>,
+    title: String,
+    detail: String,
+    suggestions: &[&str],
+) -> Finding {
+    Finding {
+        rule: rule.to_string(),
+        severity,
+        container: container.map(|container| container.to_string()),
+        title,
+        detail,
+        suggestions: suggestions.iter().map(|s| s.to_string()).collect(),
+    }
+}
+
+fn condition<'a>(pod: &'a Pod, type_: &str) -> Option<&'a PodCondition> {
+    pod.status
+        .as_ref()?
+        .conditions
+        .as_ref()?
+        .iter()
+        .find(|condition| condition.type_ == type_)
+}
+
+fn container_statuses(pod: &Pod) -> impl Iterator<Item = &ContainerStatus> {
+    let status = pod.status.as_ref();
+    status
+        .and_then(|status| status.init_container_statuses.as_ref())
+        .into_iter()
+        .flatten()
+        .chain(
+            status
+                .and_then(|status| status.container_statuses.as_ref())
+                .into_iter()
+                .flatten(),
+        )
+}
+
+fn waiting_reason(status: &ContainerStatus) -> Option<(&str, &str)> {
+    let waiting = status.state.as_ref()?.waiting.as_ref()?;
+    Some((
+        waiting.reason.as_deref().unwrap_or_default(),
+        waiting.message.as_deref().unwrap_or_default(),
+    ))
+}
+
+// 指定原因的事件，load_context 已按时间从新到旧排序
+fn events_with_reason<'a>(
+    ctx: &'a DiagnosticContext,
+    reason: &'a str,
+) -> impl Iterator<Item = &'a Event> {
+    ctx.events
+        .iter()
+        .filter(move |event| event.reason.as_deref() == Some(reason))
+}
+
+/// 解析 FailedScheduling 的消息，例如
+/// "0/3 nodes are available: 1 node(s) had untolerated taint {..}, 2 Insufficient cpu. preemption: ..."
+pub fn scheduling_reasons(message: &str) -> Vec<(u32, String)> {
+    let Some((_, reasons)) = message.split_once("are available: ") else {
+        return Vec::new();
+    };
+    // preemption 部分描述的是抢占结果，不是调度失败原因
+    let reasons = reasons.split(" preemption:").next().unwrap_or_default();
+    let reasons = reasons.trim().trim_end_matches('.');
+    let mut parsed = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    // taint 中的 {key: value} 可能包含逗号
+    for (i, c) in reasons.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                parsed.push(&reasons[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parsed.push(&reasons[start..]);
+    let mut counted: Vec<(u32, String)> = Vec::new();
+    for reason in parsed {
+        let reason = reason.trim();
+        let count = reason
+            .split_once(' ')
+            .and_then(|(count, text)| Some((count.parse().ok()?, text)));
+        match count {
+            Some((count, text)) => counted.push((count, text.to_string())),
+            // 旧版本的原因本身带逗号，例如 "had taint {..}, that the pod didn't tolerate"
+            None => {
+                if let Some((_, text)) = counted.last_mut() {
+                    text.push_str(", ");
+                    text.push_str(reason);
+                }
+            }
+        }
+    }
+    counted
+}
+
+fn scheduling_suggestion(reason: &str) -> &'static str {
+    let reason = reason.to_lowercase();
+    if reason.contains("insufficient") {
+        "Lower the pod's resource requests or add node capacity"
+    } else if reason.contains("taint") {
+        "Add a matching toleration or remove the taint from the nodes"
+    } else if reason.contains("volume node affinity") {
+        "The volume is in a different zone than the available nodes"
+    } else if reason.contains("affinity") || reason.contains("selector") {
+        "Check nodeSelector and node affinity against the node labels"
+    } else if reason.contains("persistentvolumeclaim") {
+        "Make sure the pod's PersistentVolumeClaims can be bound"
+    } else if reason.contains("free ports") {
+        "Another pod already uses the requested hostPort on those nodes"
+    } else if reason.contains("unschedulable") {
+        "Uncordon the nodes or add schedulable nodes"
+    } else if reason.contains("too many pods") {
+        "The nodes reached their pod limit, add nodes or remove pods"
+    } else {
+        "Inspect the scheduler message for this constraint"
+    }
+}
+
+fn scheduling(ctx: &DiagnosticContext) -> Vec<Finding> {
+    let unschedulable = condition(&ctx.pod, "PodScheduled")
+        .filter(|condition| condition.status == "False")
+        .and_then(|condition| condition.message.clone());
+    let message = unschedulable.or_else(|| {
+        // 已经调度成功时旧的 FailedScheduling 事件不再相关
+        if ctx
+            .pod
+            .spec
+            .as_ref()
+            .and_then(|spec| spec.node_name.as_ref())
+            .is_some()
+        {
+            return None;
+        }
+        events_with_reason(ctx, "FailedScheduling")
+            .next()
+            .and_then(|event| event.message.clone())
+    });
+    let Some(message) = message else {
+        return Vec::new();
+    };
+    let reasons = scheduling_reasons(&message);
+    let mut suggestions: Vec<&str> = reasons
+        .iter()
+        .map(|(_, reason)| scheduling_suggestion(reason))
+        .collect();
+    suggestions.sort_unstable();
+    suggestions.dedup();
+    let detail = if reasons.is_empty() {
+        message
+    } else {
+        reasons
+            .iter()
+            .map(|(count, reason)| format!("{} node(s): {}", count, reason))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    vec![finding(
+        "scheduling",
+        Severity::Critical,
+        None,
+        "Pod cannot be scheduled".to_string(),
+        detail,
+        &suggestions,
+    )]
+}
+
+fn image_pull(ctx: &DiagnosticContext) -> Vec<Finding> {
+    container_statuses(&ctx.pod)
+        .filter_map(|status| {
+            let (reason, message) = waiting_reason(status)?;
+            if !matches!(
+                reason,
+                "ErrImagePull" | "ImagePullBackOff" | "InvalidImageName"
+            ) {
+                return None;
+            }
+            let lower = message.to_lowercase();
+            let suggestions: &[&str] = if reason == "InvalidImageName" {
+                &["Fix the image reference format"]
+            } else if lower.contains("not found") || lower.contains("manifest unknown") {
+                &["Check that the image name and tag exist in the registry"]
+            } else if lower.contains("unauthorized")
+                || lower.contains("denied")
+                || lower.contains("authentication")
+            {
+                &["Configure imagePullSecrets with credentials for the registry"]
+            } else if lower.contains("timeout") || lower.contains("no such host") {
+                &["Check that the nodes can reach the registry"]
+            } else {
+                &[
+                    "Check the image name and tag",
+                    "Check imagePullSecrets and registry access from the nodes",
+                ]
+            };
+            Some(finding(
+                "image_pull",
+                Severity::Critical,
+                Some(&status.name),
+                format!("Cannot pull image {}", status.image),
+                message.to_string(),
+                suggestions,
+            ))
+        })
+        .collect()
+}
+
+fn container_config(ctx: &DiagnosticContext) -> Vec<Finding> {
+    container_statuses(&ctx.pod)
+        .filter_map(|status| {
+            let (reason, message) = waiting_reason(status)?;
+            if !matches!(
+                reason,
+                "CreateContainerConfigError" | "CreateContainerError"
+            ) {
+                return None;
+            }
+            Some(finding(
+                "container_config",
+                Severity::Critical,
+                Some(&status.name),
+                "Container cannot be created".to_string(),
+                message.to_string(),
+                &["Check that referenced ConfigMaps, Secrets and keys exist"],
+            ))
+        })
+        .collect()
+}
+
+fn exit_code_hint(exit_code: i32) -> &'static str {
+    match exit_code {
+        0 => "exited successfully but is expected to keep running",
+        1 => "application error",
+        126 => "command is not executable",
+        127 => "command not found",
+        137 => "killed by SIGKILL (OOM or liveness probe)",
+        139 => "segmentation fault",
+        143 => "terminated by SIGTERM",
+        _ => "application error",
+    }
+}
+
+fn crash_loop(ctx: &DiagnosticContext) -> Vec<Finding> {
+    container_statuses(&ctx.pod)
+        .filter_map(|status| {
+            let crashing =
+                waiting_reason(status).is_some_and(|(reason, _)| reason == "CrashLoopBackOff");
+            if !crashing && status.restart_count < RESTART_WARNING {
+                return None;
+            }
+            let last = status
+                .last_state
+                .as_ref()
+                .and_then(|state| state.terminated.as_ref());
+            let detail = match last {
+                Some(terminated) => format!(
+                    "Restarted {} times, last exit code {} ({}){}",
+                    status.restart_count,
+                    terminated.exit_code,
+                    exit_code_hint(terminated.exit_code),
+                    terminated
+                        .reason
+                        .as_ref()
+                        .map(|reason| format!(", reason {}", reason))
+                        .unwrap_or_default()
+                ),
+                None => format!("Restarted {} times", status.restart_count),
+            };
+            Some(finding(
+                "crash_loop",
+                if crashing {
+                    Severity::Critical
+                } else {
+                    Severity::Warning
+                },
+                Some(&status.name),
+                if crashing {
+                    "Container is crash looping".to_string()
+                } else {
+                    "Container restarts frequently".to_string()
+                },
+                detail,
+                &["Check the logs of the previous container instance"],
+            ))
+        })
+        .collect()
+}
+
+fn memory_limit(pod: &Pod, container: &str) -> Option<String> {
+    let spec = pod.spec.as_ref()?;
+    spec.containers
+        .iter()
+        .chain(spec.init_containers.iter().flatten())
+        .find(|c| c.name == container)?
+        .resources
+        .as_ref()?
+        .limits
+        .as_ref()?
+        .get("memory")
+        .map(|quantity| quantity.0.clone())
+}
+
+fn oom_killed(ctx: &DiagnosticContext) -> Vec<Finding> {
+    container_statuses(&ctx.pod)
+        .filter(|status| {
+            let state = |state: Option<&k8s_openapi::api::core::v1::ContainerState>| {
+                state
+                    .and_then(|state| state.terminated.as_ref())
+                    .is_some_and(|terminated| terminated.reason.as_deref() == Some("OOMKilled"))
+            };
+            state(status.state.as_ref()) || state(status.last_state.as_ref())
+        })
+        .map(|status| {
+            let detail = match memory_limit(&ctx.pod, &status.name) {
+                Some(limit) => format!("Container exceeded its memory limit of {}", limit),
+                None => "Container was killed by the kernel OOM killer without a memory limit"
+                    .to_string(),
+            };
+            finding(
+                "oom_killed",
+                Severity::Critical,
+                Some(&status.name),
+                "Container was OOMKilled".to_string(),
+                detail,
+                &[
+                    "Increase the container's memory limit",
+                    "Check the application for memory leaks",
+                ],
+            )
+        })
+        .collect()
+}
+
+fn probe_failures(ctx: &DiagnosticContext) -> Vec<Finding> {
+    let mut findings: Vec<Finding> = Vec::new();
+    for event in events_with_reason(ctx, "Unhealthy") {
+        let message = event.message.clone().unwrap_or_default();
+        let probe = ["Liveness", "Readiness", "Startup"]
+            .into_iter()
+            .find(|probe| message.starts_with(probe))
+            .unwrap_or("Health");
+        // fieldPath 形如 spec.containers{name}
+        let container = event
+            .involved_object
+            .field_path
+            .as_deref()
+            .and_then(|path| path.split_once('{'))
+            .map(|(_, rest)| rest.trim_end_matches('}').to_string());
+        if findings
+            .iter()
+            .any(|f| f.title.starts_with(probe) && f.container == container)
+        {
+            continue;
+        }
+        let suggestions: &[&str] = match probe {
+            "Liveness" => &[
+                "Check the probe path and port",
+                "Increase initialDelaySeconds or use a startupProbe for slow starts",
+            ],
+            _ => &[
+                "Check the probe path and port",
+                "Check the application's health endpoint",
+            ],
+        };
+        findings.push(finding(
+            "probe_failure",
+            if probe == "Readiness" {
+                Severity::Warning
+            } else {
+                Severity::Critical
+            },
+            container.as_deref(),
+            format!(
+                "{} probe failing ({} times)",
+                probe,
+                event.count.unwrap_or(1)
+            ),
+            message,
+            suggestions,
+        ));
+    }
+    findings
+}
+
+fn volume_claims(ctx: &DiagnosticContext) -> Vec<Finding> {
+    ctx.claims
+        .iter()
+        .filter_map(|(name, claim)| {
+            let Some(claim) = claim else {
+                return Some(finding(
+                    "volume_claim",
+                    Severity::Critical,
+                    None,
+                    format!("PersistentVolumeClaim {} not found", name),
+                    "The pod references a claim that does not exist".to_string(),
+                    &["Create the PersistentVolumeClaim or fix the volume's claimName"],
+                ));
+            };
+            let phase = claim
+                .status
+                .as_ref()
+                .and_then(|status| status.phase.as_deref())
+                .unwrap_or("Pending");
+            match phase {
+                "Bound" => None,
+                "Lost" => Some(finding(
+                    "volume_claim",
+                    Severity::Critical,
+                    None,
+                    format!("PersistentVolumeClaim {} lost its volume", name),
+                    "The bound PersistentVolume no longer exists".to_string(),
+                    &["Restore the PersistentVolume or recreate the claim"],
+                )),
+                _ => Some(finding(
+                    "volume_claim",
+                    Severity::Critical,
+                    None,
+                    format!("PersistentVolumeClaim {} is not bound", name),
+                    format!(
+                        "Claim is {} with storage class {}",
+                        phase,
+                        claim
+                            .spec
+                            .as_ref()
+                            .and_then(|spec| spec.storage_class_name.as_deref())
+                            .unwrap_or("<default>")
+                    ),
+                    &[
+                        "Check that the StorageClass exists and its provisioner is running",
+                        "For WaitForFirstConsumer the claim binds after the pod is scheduled",
+                    ],
+                )),
+            }
+        })
+        .collect()
+}
+
+fn node_conditions(ctx: &DiagnosticContext) -> Vec<Finding> {
+    let Some(node) = &ctx.node else {
+        return Vec::new();
+    };
+    let mut findings = Vec::new();
+    for condition in node
+        .status
+        .as_ref()
+        .and_then(|status| status.conditions.as_ref())
+        .into_iter()
+        .flatten()
+    {
+        let unhealthy = match condition.type_.as_str() {
+            "Ready" => condition.status != "True",
+            "MemoryPressure" | "DiskPressure" | "PIDPressure" | "NetworkUnavailable" => {
+                condition.status == "True"
+            }
+            _ => false,
+        };
+        if !unhealthy {
+            continue;
+        }
+        findings.push(finding(
+            "node_condition",
+            if condition.type_ == "Ready" {
+                Severity::Critical
+            } else {
+                Severity::Warning
+            },
+            None,
+            format!(
+                "Node {} has condition {}={}",
+                node.name_any(),
+                condition.type_,
+                condition.status
+            ),
+            condition.message.clone().unwrap_or_default(),
+            &["Check the node's kubelet and resource usage"],
+        ));
+    }
+    if node.spec.as_ref().and_then(|spec| spec.unschedulable) == Some(true) {
+        findings.push(finding(
+            "node_condition",
+            Severity::Info,
+            None,
+            format!("Node {} is cordoned", node.name_any()),
+            "New pods will not be scheduled on this node".to_string(),
+            &[],
+        ));
+    }
+    findings
+}
+
+fn evicted(ctx: &DiagnosticContext) -> Vec<Finding> {
+    let Some(status) = &ctx.pod.status else {
+        return Vec::new();
+    };
+    if status.reason.as_deref() != Some("Evicted") {
+        return Vec::new();
+    }
+    vec![finding(
+        "evicted",
+        Severity::Warning,
+        None,
+        "Pod was evicted".to_string(),
+        status.message.clone().unwrap_or_default(),
+        &["Set resource requests so the pod is not evicted first under node pressure"],
+    )]
+}
+
+// 与 examples/test_watch.rs 的 pod_unready 一致，其它规则没有结论时才报告
+fn unready(ctx: &DiagnosticContext) -> Vec<Finding> {
+    let phase = ctx
+        .pod
+        .status
+        .as_ref()
+        .and_then(|status| status.phase.as_deref());
+    if phase == Some("Succeeded") {
+        return Vec::new();
+    }
+    let Some(ready) = condition(&ctx.pod, "Ready").filter(|condition| condition.status == "False")
+    else {
+        return Vec::new();
+    };
+    vec![finding(
+        "unready",
+        Severity::Info,
+        None,
+        "Pod is not ready".to_string(),
+        ready
+            .message
+            .clone()
+            .or_else(|| ready.reason.clone())
+            .unwrap_or_default(),
+        &[],
+    )]
+}
+
+fn claim_names(pod: &Pod) -> Vec<String> {
+    pod.spec
+        .iter()
+        .flat_map(|spec| spec.volumes.iter().flatten())
+        .filter_map(|volume| volume.persistent_volume_claim.as_ref())
+        .map(|claim| claim.claim_name.clone())
+        .collect()
+}
+
+/// 读取 pod 相关的事件、节点和 PVC
+pub async fn load_context(
+    client: Client,
+    namespace: &str,
+    name: &str,
+) -> Result<DiagnosticContext, MyError> {
+    let pod = Api::<Pod>::namespaced(client.clone(), namespace)
+        .get(name)
+        .await?;
+    let selector = format!(
+        "involvedObject.kind=Pod,involvedObject.name={},involvedObject.uid={}",
+        name,
+        pod.uid().unwrap_or_default()
+    );
+    let mut events = match Api::<Event>::namespaced(client.clone(), namespace)
+        .list(&ListParams::default().fields(&selector))
+        .await
+    {
+        Ok(list) => list.items,
+        Err(e) => {
+            // 没有读取事件的权限时只跳过依赖事件的规则
+            tracing::debug!("Failed to list events for pod {}: {}", name, e);
+            Vec::new()
+        }
+    };
+    events.sort_by_key(|event| {
+        std::cmp::Reverse(
+            event
+                .last_timestamp
+                .as_ref()
+                .map(|time| time.0)
+                .or_else(|| event.event_time.as_ref().map(|time| time.0)),
+        )
+    });
+
+    let node = match pod.spec.as_ref().and_then(|spec| spec.node_name.as_deref()) {
+        Some(node) => Api::<Node>::all(client.clone())
+            .get_opt(node)
+            .await
+            .unwrap_or_else(|e| {
+                // 没有读取节点的权限时跳过节点规则
+                tracing::debug!("Failed to read node {}: {}", node, e);
+                None
+            }),
+        None => None,
+    };
+    let claims_api = Api::<PersistentVolumeClaim>::namespaced(client, namespace);
+    let mut claims = Vec::new();
+    for claim in claim_names(&pod) {
+        match claims_api.get_opt(&claim).await {
+            Ok(found) => claims.push((claim, found)),
+            // 读取失败时不能断定 PVC 不存在，跳过该 PVC
+            Err(e) => tracing::debug!("Failed to read PVC {}: {}", claim, e),
+        }
+    }
+    Ok(DiagnosticContext {
+        pod,
+        events,
+        node,
+        claims,
+    })
+}
+
+/// 依次执行所有规则，unready 只在其它规则没有结论时保留
+pub fn evaluate(ctx: &DiagnosticContext) -> PodDiagnostics {
+    let mut findings: Vec<Finding> = RULES.iter().flat_map(|rule| rule(ctx)).collect();
+    if findings.iter().any(|finding| finding.rule != "unready") {
+        findings.retain(|finding| finding.rule != "unready");
+    }
+    findings.sort_by_key(|finding| std::cmp::Reverse(finding.severity));
+    let status = ctx.pod.status.as_ref();
+    PodDiagnostics {
+        namespace: ctx.pod.namespace().unwrap_or_default(),
+        name: ctx.pod.name_any(),
+        phase: status.and_then(|status| status.phase.clone()),
+        node: ctx
+            .pod
+            .spec
+            .as_ref()
+            .and_then(|spec| spec.node_name.clone()),
+        ready: condition(&ctx.pod, "Ready").is_some_and(|condition| condition.status == "True"),
+        findings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::scheduling_reasons;
+
+    #[test]
+    fn parses_scheduling_reasons() {
+        let cases: &[(&str, &[(u32, &str)])] = &[
+            (
+                "0/3 nodes are available: 3 Insufficient memory.",
+                &[(3, "Insufficient memory")],
+            ),
+            (
+                "0/3 nodes are available: 1 node(s) had untolerated taint {node-role.kubernetes.io/control-plane: }, 2 Insufficient cpu. preemption: 0/3 nodes are available: 1 Preemption is not helpful for scheduling, 2 No preemption victims found for incoming pod.",
+                &[
+                    (
+                        1,
+                        "node(s) had untolerated taint {node-role.kubernetes.io/control-plane: }",
+                    ),
+                    (2, "Insufficient cpu"),
+                ],
+            ),
+            (
+                "0/5 nodes are available: 1 node(s) had untolerated taint {dedicated: gpu}, 1 node(s) had untolerated taint {node.kubernetes.io/unreachable: }, 3 node(s) didn't match Pod's node affinity/selector. preemption: 0/5 nodes are available: 5 Preemption is not helpful for scheduling.",
+                &[
+                    (1, "node(s) had untolerated taint {dedicated: gpu}"),
+                    (
+                        1,
+                        "node(s) had untolerated taint {node.kubernetes.io/unreachable: }",
+                    ),
+                    (3, "node(s) didn't match Pod's node affinity/selector"),
+                ],
+            ),
+            (
+                "0/2 nodes are available: 1 node(s) had taint {node.kubernetes.io/not-ready: }, that the pod didn't tolerate, 1 node(s) had volume node affinity conflict.",
+                &[
+                    (
+                        1,
+                        "node(s) had taint {node.kubernetes.io/not-ready: }, that the pod didn't tolerate",
+                    ),
+                    (1, "node(s) had volume node affinity conflict"),
+                ],
+            ),
+            (
+                "0/1 nodes are available: 1 pod has unbound immediate PersistentVolumeClaims. preemption: 0/1 nodes are available: 1 Preemption is not helpful for scheduling.",
+                &[(1, "pod has unbound immediate PersistentVolumeClaims")],
+            ),
+            ("no nodes available to schedule pods", &[]),
+        ];
+        for (message, expected) in cases {
+            let expected: Vec<(u32, String)> = expected
+                .iter()
+                .map(|(count, text)| (*count, text.to_string()))
+                .collect();
+            assert_eq!(scheduling_reasons(message), expected, "{}", message);
+        }
+    }
+}
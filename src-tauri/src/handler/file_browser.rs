@@ -0,0 +1,373 @@
+use chrono::{Datelike, NaiveDateTime, Utc};
+use k8s_openapi::api::core::v1::Pod;
+use kube::api::{Api, AttachParams};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::{
+    error::MyError,
+    handler::{
+        copy::{exec_failure, read_to_string},
+        terminal,
+    },
+    resource::file::{FileContent, FileEntry, FileKind},
+};
+
+pub const DEFAULT_READ_LIMIT: usize = 1024 * 1024;
+// 单次 stat 的参数个数，避免超出命令行长度限制
+const STAT_BATCH: usize = 256;
+// 模式(十六进制)|大小|修改时间|路径，路径放最后以容忍文件名中的 |
+const STAT_FORMAT: &str = "%f|%s|%Y|%n";
+
+struct ExecOutput {
+    stdout: Vec<u8>,
+    truncated: bool,
+}
+
+// 执行命令并收集输出，stdout 超过 limit 时截断并结束进程
+async fn exec_capture(
+    pods: &Api<Pod>,
+    pod: &str,
+    container: &str,
+    command: Vec<String>,
+    stdin: Option<Vec<u8>>,
+    limit: Option<usize>,
+) -> Result<ExecOutput, MyError> {
+    let (output, failure) = exec_output(pods, pod, container, command, stdin, limit).await?;
+    failure.map_or(Ok(output), Err)
+}
+
+// 同 exec_capture，但命令失败时仍返回已有的输出，失败原因单独返回
+async fn exec_output(
+    pods: &Api<Pod>,
+    pod: &str,
+    container: &str,
+    command: Vec<String>,
+    stdin: Option<Vec<u8>>,
+    limit: Option<usize>,
+) -> Result<(ExecOutput, Option<MyError>), MyError> {
+    let program = command.first().cloned().unwrap_or_default();
+    let params = AttachParams::default()
+        .container(container)
+        .stdin(stdin.is_some())
+        .stdout(true)
+        .stderr(true);
+    let mut attached = pods
+        .exec(pod, command, &params)
+        .await
+        .map_err(terminal::exec_error)?;
+
+    let stdin_task = match (stdin, attached.stdin()) {
+        (Some(data), Some(mut writer)) => Some(tokio::spawn(async move {
+            writer.write_all(&data).await?;
+            writer.shutdown().await
+        })),
+        _ => None,
+    };
+    let mut stdout = attached
+        .stdout()
+        .ok_or_else(|| MyError::KubeError("No stdout available".to_string()))?;
+    let stderr_task = tokio::spawn(read_to_string(attached.stderr()));
+    let status = attached.take_status();
+
+    let mut output = Vec::new();
+    // 多读一个字节用来判断是否超过上限
+    let read_limit = limit.map_or(u64::MAX, |limit| limit as u64 + 1);
+    (&mut stdout)
+        .take(read_limit)
+        .read_to_end(&mut output)
+        .await?;
+    if let Some(limit) = limit.filter(|limit| output.len() > *limit) {
+        output.truncate(limit);
+        attached.abort();
+        return Ok((
+            ExecOutput {
+                stdout: output,
+                truncated: true,
+            },
+            None,
+        ));
+    }
+
+    let status = match status {
+        Some(status) => status.await,
+        None => None,
+    };
+    let stderr = stderr_task.await.unwrap_or_default();
+    let failure = exec_failure(status, &stderr, &program, container);
+    if let (None, Some(stdin_task)) = (&failure, stdin_task) {
+        stdin_task
+            .await
+            .map_err(|e| MyError::OtherError(e.to_string()))??;
+    }
+    Ok((
+        ExecOutput {
+            stdout: output,
+            truncated: false,
+        },
+        failure,
+    ))
+}
+
+fn file_kind(mode: u32) -> FileKind {
+    match mode & 0o170000 {
+        0o040000 => FileKind::Directory,
+        0o100000 => FileKind::File,
+        0o120000 => FileKind::Symlink,
+        _ => FileKind::Other,
+    }
+}
+
+fn permissions(kind: FileKind, mode: u32) -> String {
+    let mut text = String::with_capacity(10);
+    text.push(match kind {
+        FileKind::Directory => 'd',
+        FileKind::Symlink => 'l',
+        FileKind::File => '-',
+        FileKind::Other => '?',
+    });
+    for shift in [6, 3, 0] {
+        let bits = (mode >> shift) & 0o7;
+        text.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        text.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        text.push(if bits & 0o1 != 0 { 'x' } else { '-' });
+    }
+    text
+}
+
+fn join_path(dir: &str, name: &str) -> String {
+    format!("{}/{}", dir.trim_end_matches('/'), name)
+}
+
+fn parse_stat_line(dir: &str, line: &str) -> Option<FileEntry> {
+    let mut fields = line.splitn(4, '|');
+    let raw_mode = u32::from_str_radix(fields.next()?, 16).ok()?;
+    let size = fields.next()?.parse().ok()?;
+    let mtime = fields.next()?.parse().ok();
+    let path = fields.next()?;
+    let name = path.rsplit_once('/').map_or(path, |(_, name)| name);
+    let kind = file_kind(raw_mode);
+    Some(FileEntry {
+        name: name.to_string(),
+        path: join_path(dir, name),
+        kind,
+        size,
+        mode: raw_mode & 0o7777,
+        permissions: permissions(kind, raw_mode),
+        mtime,
+    })
+}
+
+// ls 的日期列：半年内的文件显示 "月 日 时:分"，更早的显示 "月 日 年"，按 UTC 处理
+fn parse_ls_time(month: &str, day: &str, time_or_year: &str) -> Option<i64> {
+    let now = Utc::now();
+    let (year, time) = if time_or_year.contains(':') {
+        (now.year(), time_or_year)
+    } else {
+        (time_or_year.parse().ok()?, "00:00")
+    };
+    let parse = |year: i32| {
+        NaiveDateTime::parse_from_str(
+            &format!("{} {} {} {}", year, month, day, time),
+            "%Y %b %d %H:%M",
+        )
+        .ok()
+    };
+    let mut mtime = parse(year)?;
+    // 省略年份时日期在未来说明是去年的文件
+    if time_or_year.contains(':') && mtime > now.naive_utc() + chrono::Duration::days(1) {
+        mtime = parse(year - 1)?;
+    }
+    Some(mtime.and_utc().timestamp())
+}
+
+// 解析 `ls -lan` 的一行：权限 链接数 uid gid 大小 月 日 时间/年份 文件名
+// 设备文件的大小列是 "主设备号, 次设备号" 两个字段
+fn parse_ls_line(dir: &str, line: &str) -> Option<FileEntry> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let perm = *fields.first()?;
+    let device = perm.starts_with('c') || perm.starts_with('b');
+    let date = if device { 6 } else { 5 };
+    if fields.len() < date + 4 {
+        return None;
+    }
+    let kind = match perm.chars().next()? {
+        'd' => FileKind::Directory,
+        '-' => FileKind::File,
+        'l' => FileKind::Symlink,
+        _ => FileKind::Other,
+    };
+    let mut mode = 0u32;
+    for (i, c) in perm.chars().skip(1).take(9).enumerate() {
+        // 执行位上的 s/t 表示同时可执行，S/T 表示只有 setuid/setgid/sticky 没有执行权限
+        if !matches!(c, '-' | 'S' | 'T') {
+            mode |= 1 << (8 - i);
+        }
+        if matches!(c, 's' | 'S' | 't' | 'T') {
+            mode |= 0o4000 >> (i / 3);
+        }
+    }
+    // 文件名从原始行截取，保留其中连续的空格
+    let offset = fields[date + 3].as_ptr() as usize - line.as_ptr() as usize;
+    let mut name = &line[offset..];
+    if kind == FileKind::Symlink {
+        if let Some((link, _)) = name.split_once(" -> ") {
+            name = link;
+        }
+    }
+    if name == "." || name == ".." {
+        return None;
+    }
+    Some(FileEntry {
+        path: join_path(dir, name),
+        name: name.to_string(),
+        kind,
+        size: if device {
+            0
+        } else {
+            fields[4].parse().unwrap_or_default()
+        },
+        mode,
+        permissions: perm.chars().take(10).collect(),
+        mtime: parse_ls_time(fields[date], fields[date + 1], fields[date + 2]),
+    })
+}
+
+fn args(command: &[&str]) -> Vec<String> {
+    command.iter().map(|arg| arg.to_string()).collect()
+}
+
+/// 列出容器内目录，优先用 stat 获取精确信息，没有 stat 时退回解析 ls -l
+pub async fn list_dir(
+    pods: &Api<Pod>,
+    pod: &str,
+    container: &str,
+    dir: &str,
+) -> Result<Vec<FileEntry>, MyError> {
+    // 末尾的 / 让 ls 在路径不是目录时报错
+    let dir_arg = format!("{}/", dir.trim_end_matches('/'));
+    let names = exec_capture(
+        pods,
+        pod,
+        container,
+        args(&["ls", "-1a", &dir_arg]),
+        None,
+        None,
+    )
+    .await?;
+    let names: Vec<String> = String::from_utf8_lossy(&names.stdout)
+        .lines()
+        .filter(|name| !name.is_empty() && *name != "." && *name != "..")
+        .map(|name| name.to_string())
+        .collect();
+
+    let mut entries = Vec::with_capacity(names.len());
+    for batch in names.chunks(STAT_BATCH) {
+        let mut command = args(&["stat", "-c", STAT_FORMAT, "--"]);
+        command.extend(batch.iter().map(|name| join_path(dir, name)));
+        let output = match exec_output(pods, pod, container, command, None, None).await? {
+            (output, None) => output,
+            (_, Some(MyError::MissingCommand(_))) => {
+                tracing::info!(
+                    "stat not available in {}/{}, falling back to ls",
+                    pod,
+                    container
+                );
+                let output = exec_capture(
+                    pods,
+                    pod,
+                    container,
+                    args(&["ls", "-lan", &dir_arg]),
+                    None,
+                    None,
+                )
+                .await?;
+                let mut entries: Vec<FileEntry> = String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .filter_map(|line| parse_ls_line(dir, line))
+                    .collect();
+                sort_entries(&mut entries);
+                return Ok(entries);
+            }
+            // 个别文件在 ls 之后被删除或无权访问时 stat 返回非 0，其余文件的结果仍然有效
+            (output, Some(e)) => {
+                tracing::debug!("stat failed for some entries in {}: {}", dir, e);
+                output
+            }
+        };
+        entries.extend(
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter_map(|line| parse_stat_line(dir, line)),
+        );
+    }
+    sort_entries(&mut entries);
+    Ok(entries)
+}
+
+// 目录在前，其余按名称排序
+fn sort_entries(entries: &mut [FileEntry]) {
+    entries.sort_by(|a, b| {
+        (b.kind == FileKind::Directory)
+            .cmp(&(a.kind == FileKind::Directory))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+}
+
+/// 用 cat 读取文件，超过 limit 的部分被截断
+pub async fn read_file(
+    pods: &Api<Pod>,
+    pod: &str,
+    container: &str,
+    path: &str,
+    limit: usize,
+) -> Result<FileContent, MyError> {
+    let output = exec_capture(
+        pods,
+        pod,
+        container,
+        args(&["cat", "--", path]),
+        None,
+        Some(limit),
+    )
+    .await?;
+    let bytes = output.stdout.len();
+    let content = match String::from_utf8(output.stdout) {
+        Ok(text) => Some(text),
+        // 截断可能切开多字节字符，只丢弃末尾不完整的部分
+        Err(e) if output.truncated && e.utf8_error().error_len().is_none() => {
+            let valid = e.utf8_error().valid_up_to();
+            let mut bytes = e.into_bytes();
+            bytes.truncate(valid);
+            String::from_utf8(bytes).ok()
+        }
+        Err(_) => None,
+    };
+    Ok(FileContent {
+        path: path.to_string(),
+        binary: content.is_none(),
+        content,
+        bytes,
+        truncated: output.truncated,
+    })
+}
+
+/// 通过 stdin 交给 dd 写入文件，不依赖容器内的 shell
+pub async fn write_file(
+    pods: &Api<Pod>,
+    pod: &str,
+    container: &str,
+    path: &str,
+    content: Vec<u8>,
+) -> Result<(), MyError> {
+    let of = format!("of={}", path);
+    exec_capture(
+        pods,
+        pod,
+        container,
+        args(&["dd", &of]),
+        Some(content),
+        None,
+    )
+    .await?;
+    Ok(())
+}
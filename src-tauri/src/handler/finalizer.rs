@@ -0,0 +1,151 @@
+use std::collections::BTreeSet;
+
+use futures::future::join_all;
+use k8s_openapi::api::core::v1::Namespace;
+use kube::{
+    api::{Api, ListParams, Patch, PatchParams, PostParams},
+    discovery::{ApiCapabilities, ApiResource, Scope},
+    Client,
+};
+
+use crate::{
+    error::MyError,
+    resource::finalizer::{FinalizerDiagnostics, RemainingResource, StatusCondition},
+    utils::discovery::dynamic_api,
+};
+
+const REMAINING_NAMES_LIMIT: usize = 20;
+
+fn is_namespace(resource: &ApiResource) -> bool {
+    resource.group.is_empty() && resource.kind == "Namespace"
+}
+
+// 命名空间中仍有对象的资源类型，无权限列出的类型跳过
+async fn remaining_resources(
+    client: Client,
+    resources: &[(ApiResource, ApiCapabilities)],
+    namespace: &str,
+) -> Vec<RemainingResource> {
+    let lists = resources
+        .iter()
+        .filter(|(_, capabilities)| capabilities.scope == Scope::Namespaced)
+        .map(|(resource, capabilities)| {
+            let api = dynamic_api(client.clone(), resource, capabilities, Some(namespace));
+            async move { (resource, api.list_metadata(&ListParams::default()).await) }
+        });
+
+    let mut remaining = Vec::new();
+    for (resource, list) in join_all(lists).await {
+        let items = match list {
+            Ok(list) if !list.items.is_empty() => list.items,
+            Ok(_) => continue,
+            Err(e) => {
+                tracing::debug!("Skipping {} in {}: {}", resource.plural, namespace, e);
+                continue;
+            }
+        };
+        let finalizers: BTreeSet<String> = items
+            .iter()
+            .flat_map(|item| item.metadata.finalizers.iter().flatten().cloned())
+            .collect();
+        remaining.push(RemainingResource {
+            api_version: resource.api_version.clone(),
+            kind: resource.kind.clone(),
+            count: items.len(),
+            names: items
+                .iter()
+                .take(REMAINING_NAMES_LIMIT)
+                .filter_map(|item| item.metadata.name.clone())
+                .collect(),
+            finalizers: finalizers.into_iter().collect(),
+        });
+    }
+    remaining.sort_by(|a, b| a.kind.cmp(&b.kind));
+    remaining
+}
+
+/// 列出对象剩余的 finalizer；命名空间额外报告状态条件和仍有对象的资源类型
+pub async fn diagnose(
+    client: Client,
+    resources: &[(ApiResource, ApiCapabilities)],
+    resource: &ApiResource,
+    capabilities: &ApiCapabilities,
+    namespace: Option<&str>,
+    name: &str,
+) -> Result<FinalizerDiagnostics, MyError> {
+    let object = dynamic_api(client.clone(), resource, capabilities, namespace)
+        .get(name)
+        .await?;
+    let mut diagnostics = FinalizerDiagnostics {
+        kind: resource.kind.clone(),
+        namespace: object.metadata.namespace.clone(),
+        name: name.to_string(),
+        terminating: object.metadata.deletion_timestamp.is_some(),
+        deletion_timestamp: object
+            .metadata
+            .deletion_timestamp
+            .as_ref()
+            .map(|time| time.0),
+        finalizers: object.metadata.finalizers.clone().unwrap_or_default(),
+        spec_finalizers: Vec::new(),
+        conditions: Vec::new(),
+        remaining: Vec::new(),
+    };
+    if !is_namespace(resource) {
+        return Ok(diagnostics);
+    }
+
+    let ns = Api::<Namespace>::all(client.clone()).get(name).await?;
+    diagnostics.spec_finalizers = ns.spec.and_then(|spec| spec.finalizers).unwrap_or_default();
+    diagnostics.conditions = ns
+        .status
+        .and_then(|status| status.conditions)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|condition| StatusCondition {
+            type_: condition.type_,
+            status: condition.status,
+            reason: condition.reason,
+            message: condition.message,
+        })
+        .collect();
+    diagnostics.remaining = remaining_resources(client, resources, name).await;
+    Ok(diagnostics)
+}
+
+/// 清空正在删除的对象的 metadata.finalizers；命名空间还会通过 finalize 子资源清空 spec.finalizers
+pub async fn remove_finalizers(
+    client: Client,
+    resource: &ApiResource,
+    capabilities: &ApiCapabilities,
+    namespace: Option<&str>,
+    name: &str,
+) -> Result<(), MyError> {
+    let api = dynamic_api(client.clone(), resource, capabilities, namespace);
+    // 未删除的对象上的 finalizer 仍在起保护作用，例如 pvc-protection
+    if api.get(name).await?.metadata.deletion_timestamp.is_none() {
+        return Err(MyError::OtherError(format!(
+            "{} {} is not being deleted",
+            resource.kind, name
+        )));
+    }
+    let patch = serde_json::json!({ "metadata": { "finalizers": null } });
+    api.patch(name, &PatchParams::default(), &Patch::Merge(&patch))
+        .await?;
+
+    if is_namespace(resource) {
+        let namespaces = Api::<Namespace>::all(client);
+        // 对象已被删除时说明只剩 metadata 中的 finalizer
+        let Some(mut ns) = namespaces.get_opt(name).await? else {
+            return Ok(());
+        };
+        if let Some(spec) = ns.spec.as_mut() {
+            spec.finalizers = None;
+        }
+        let data = serde_json::to_vec(&ns).map_err(|e| MyError::OtherError(e.to_string()))?;
+        namespaces
+            .replace_subresource("finalize", name, &PostParams::default(), data)
+            .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,632 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    hash::Hash,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures::{future, Stream, StreamExt};
+use k8s_openapi::{
+    api::{
+        apps::v1::{DaemonSet, Deployment, ReplicaSet, StatefulSet},
+        batch::v1::{CronJob, Job},
+        core::v1::{ConfigMap, PersistentVolumeClaim, Pod, Secret, Service, ServiceAccount},
+        networking::v1::Ingress,
+    },
+    apimachinery::pkg::apis::meta::v1::{ObjectMeta, OwnerReference},
+};
+use kube::{
+    api::{Api, PartialObjectMeta},
+    discovery::{ApiCapabilities, ApiResource},
+    runtime::{
+        metadata_watcher,
+        reflector::{self, Store},
+        watcher, WatchStreamExt,
+    },
+    Client, Resource, ResourceExt,
+};
+use serde::de::DeserializeOwned;
+use tokio::{sync::OnceCell, task::JoinHandle};
+
+use crate::{
+    error::MyError,
+    resource::graph::{GraphEdge, GraphNode, GraphRelation, ResourceGraph},
+    utils::discovery::dynamic_api,
+};
+
+// 命名空间缓存超过该时间未使用则停止 watch
+const CACHE_IDLE: Duration = Duration::from_secs(300);
+// 等待首次 list 完成的时间，无权限或失败的资源按空列表处理
+const CACHE_READY_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn spawn_reflector<K, S>(stream: S, tasks: &mut Vec<JoinHandle<()>>) -> Store<K>
+where
+    K: Resource + Clone + Send + Sync + 'static,
+    K::DynamicType: Default + Eq + Hash + Clone + Send + Sync,
+    S: Stream<Item = watcher::Result<watcher::Event<K>>> + Send + 'static,
+{
+    let (reader, writer) = reflector::store();
+    let stream = reflector::reflector(writer, stream.default_backoff());
+    tasks.push(tokio::spawn(stream.for_each(|event| {
+        if let Err(e) = event {
+            tracing::debug!("Relationship graph watch error: {}", e);
+        }
+        future::ready(())
+    })));
+    reader
+}
+
+fn watch_objects<K>(api: Api<K>, tasks: &mut Vec<JoinHandle<()>>) -> Store<K>
+where
+    K: Resource<DynamicType = ()> + Clone + Debug + DeserializeOwned + Send + Sync + 'static,
+{
+    spawn_reflector(watcher(api, watcher::Config::default()), tasks)
+}
+
+// 只需要 ownerReferences 和名称的资源只 watch 元数据，Secret 也不会读取内容
+fn watch_metadata<K>(api: Api<K>, tasks: &mut Vec<JoinHandle<()>>) -> Store<PartialObjectMeta<K>>
+where
+    K: Resource<DynamicType = ()> + Clone + Debug + DeserializeOwned + Send + Sync + 'static,
+{
+    spawn_reflector(metadata_watcher(api, watcher::Config::default()), tasks)
+}
+
+// 一个命名空间内关系图用到的资源缓存，由 reflector 持续更新
+struct NamespaceCache {
+    deployments: Store<PartialObjectMeta<Deployment>>,
+    replica_sets: Store<PartialObjectMeta<ReplicaSet>>,
+    statefulsets: Store<PartialObjectMeta<StatefulSet>>,
+    daemonsets: Store<PartialObjectMeta<DaemonSet>>,
+    jobs: Store<PartialObjectMeta<Job>>,
+    cron_jobs: Store<PartialObjectMeta<CronJob>>,
+    pods: Store<Pod>,
+    services: Store<Service>,
+    ingresses: Store<Ingress>,
+    config_maps: Store<PartialObjectMeta<ConfigMap>>,
+    secrets: Store<PartialObjectMeta<Secret>>,
+    claims: Store<PartialObjectMeta<PersistentVolumeClaim>>,
+    service_accounts: Store<PartialObjectMeta<ServiceAccount>>,
+    ready: OnceCell<()>,
+    last_used: Mutex<Instant>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl NamespaceCache {
+    fn start(client: Client, namespace: &str) -> NamespaceCache {
+        let mut tasks = Vec::new();
+        NamespaceCache {
+            deployments: watch_metadata(Api::namespaced(client.clone(), namespace), &mut tasks),
+            replica_sets: watch_metadata(Api::namespaced(client.clone(), namespace), &mut tasks),
+            statefulsets: watch_metadata(Api::namespaced(client.clone(), namespace), &mut tasks),
+            daemonsets: watch_metadata(Api::namespaced(client.clone(), namespace), &mut tasks),
+            jobs: watch_metadata(Api::namespaced(client.clone(), namespace), &mut tasks),
+            cron_jobs: watch_metadata(Api::namespaced(client.clone(), namespace), &mut tasks),
+            pods: watch_objects(Api::namespaced(client.clone(), namespace), &mut tasks),
+            services: watch_objects(Api::namespaced(client.clone(), namespace), &mut tasks),
+            ingresses: watch_objects(Api::namespaced(client.clone(), namespace), &mut tasks),
+            config_maps: watch_metadata(Api::namespaced(client.clone(), namespace), &mut tasks),
+            secrets: watch_metadata(Api::namespaced(client.clone(), namespace), &mut tasks),
+            claims: watch_metadata(Api::namespaced(client.clone(), namespace), &mut tasks),
+            service_accounts: watch_metadata(Api::namespaced(client, namespace), &mut tasks),
+            ready: OnceCell::new(),
+            last_used: Mutex::new(Instant::now()),
+            tasks,
+        }
+    }
+
+    // 只在首次使用时等待，之后直接读取缓存
+    async fn wait_until_ready(&self) {
+        self.ready
+            .get_or_init(|| async {
+                let ready = async {
+                    tokio::join!(
+                        self.deployments.wait_until_ready(),
+                        self.replica_sets.wait_until_ready(),
+                        self.statefulsets.wait_until_ready(),
+                        self.daemonsets.wait_until_ready(),
+                        self.jobs.wait_until_ready(),
+                        self.cron_jobs.wait_until_ready(),
+                        self.pods.wait_until_ready(),
+                        self.services.wait_until_ready(),
+                        self.ingresses.wait_until_ready(),
+                        self.config_maps.wait_until_ready(),
+                        self.secrets.wait_until_ready(),
+                        self.claims.wait_until_ready(),
+                        self.service_accounts.wait_until_ready(),
+                    )
+                };
+                if tokio::time::timeout(CACHE_READY_TIMEOUT, ready)
+                    .await
+                    .is_err()
+                {
+                    tracing::debug!(
+                        "Relationship graph cache not fully synced, using partial lists"
+                    );
+                }
+            })
+            .await;
+    }
+
+    fn idle(&self) -> bool {
+        self.last_used.lock().unwrap().elapsed() >= CACHE_IDLE
+    }
+}
+
+impl Drop for NamespaceCache {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+/// 关系图的资源缓存，按命名空间启动 watch，空闲一段时间后停止，切换集群时清空
+#[derive(Clone, Default)]
+pub struct GraphCache {
+    namespaces: Arc<Mutex<HashMap<String, Arc<NamespaceCache>>>>,
+}
+
+impl GraphCache {
+    fn namespace(&self, client: Client, namespace: &str) -> Arc<NamespaceCache> {
+        let cache = {
+            let mut namespaces = self.namespaces.lock().unwrap();
+            namespaces
+                .entry(namespace.to_string())
+                .or_insert_with(|| Arc::new(NamespaceCache::start(client, namespace)))
+                .clone()
+        };
+        *cache.last_used.lock().unwrap() = Instant::now();
+
+        // 空闲超时后移除，期间再次使用会刷新 last_used
+        let namespaces = self.namespaces.clone();
+        let namespace = namespace.to_string();
+        tokio::spawn(async move {
+            tokio::time::sleep(CACHE_IDLE).await;
+            let mut namespaces = namespaces.lock().unwrap();
+            if namespaces.get(&namespace).is_some_and(|cache| cache.idle()) {
+                namespaces.remove(&namespace);
+            }
+        });
+        cache
+    }
+
+    pub fn clear(&self) {
+        self.namespaces.lock().unwrap().clear();
+    }
+}
+
+// 从缓存中取出的命名空间快照，之后的关系都在内存中计算
+struct Snapshot {
+    // 可能出现在 ownerReferences 链上的对象
+    objects: Vec<(String, ObjectMeta)>,
+    pods: Vec<Arc<Pod>>,
+    services: Vec<Arc<Service>>,
+    ingresses: Vec<Arc<Ingress>>,
+    // kind -> 名称 -> uid
+    referenced: HashMap<&'static str, HashMap<String, Option<String>>>,
+}
+
+fn metas<K: Resource<DynamicType = ()> + Clone>(
+    store: &Store<PartialObjectMeta<K>>,
+) -> impl Iterator<Item = (String, ObjectMeta)> {
+    store
+        .state()
+        .into_iter()
+        .map(|item| (K::kind(&()).to_string(), item.metadata.clone()))
+}
+
+fn names<K: Resource<DynamicType = ()> + Clone>(
+    store: &Store<PartialObjectMeta<K>>,
+) -> HashMap<String, Option<String>> {
+    store
+        .state()
+        .into_iter()
+        .map(|item| (item.name_any(), item.uid()))
+        .collect()
+}
+
+impl Snapshot {
+    async fn load(cache: &NamespaceCache) -> Snapshot {
+        cache.wait_until_ready().await;
+
+        let pods = cache.pods.state();
+        let mut objects = Vec::new();
+        objects.extend(metas(&cache.deployments));
+        objects.extend(metas(&cache.replica_sets));
+        objects.extend(metas(&cache.statefulsets));
+        objects.extend(metas(&cache.daemonsets));
+        objects.extend(metas(&cache.jobs));
+        objects.extend(metas(&cache.cron_jobs));
+        objects.extend(
+            pods.iter()
+                .map(|pod| (Pod::kind(&()).to_string(), pod.metadata.clone())),
+        );
+        Snapshot {
+            objects,
+            pods,
+            services: cache.services.state(),
+            ingresses: cache.ingresses.state(),
+            referenced: HashMap::from([
+                ("ConfigMap", names(&cache.config_maps)),
+                ("Secret", names(&cache.secrets)),
+                ("PersistentVolumeClaim", names(&cache.claims)),
+                ("ServiceAccount", names(&cache.service_accounts)),
+            ]),
+        }
+    }
+
+    fn object_by_uid(&self, uid: &str) -> Option<&(String, ObjectMeta)> {
+        self.objects
+            .iter()
+            .find(|(_, meta)| meta.uid.as_deref() == Some(uid))
+    }
+
+    fn owned_by<'a>(&'a self, uid: &'a str) -> impl Iterator<Item = &'a (String, ObjectMeta)> {
+        self.objects.iter().filter(move |(_, meta)| {
+            meta.owner_references
+                .iter()
+                .flatten()
+                .any(|owner| owner.uid == uid)
+        })
+    }
+}
+
+fn node_id(kind: &str, namespace: Option<&str>, name: &str) -> String {
+    format!("{}/{}/{}", kind, namespace.unwrap_or_default(), name)
+}
+
+fn selects(service: &Service, pod: &Pod) -> bool {
+    let Some(selector) = service
+        .spec
+        .as_ref()
+        .and_then(|spec| spec.selector.as_ref())
+        .filter(|selector| !selector.is_empty())
+    else {
+        return false;
+    };
+    let labels = pod.labels();
+    selector
+        .iter()
+        .all(|(key, value)| labels.get(key) == Some(value))
+}
+
+fn ingress_services(ingress: &Ingress) -> HashSet<String> {
+    let Some(spec) = ingress.spec.as_ref() else {
+        return HashSet::new();
+    };
+    let default = spec
+        .default_backend
+        .as_ref()
+        .and_then(|backend| backend.service.as_ref());
+    let paths = spec
+        .rules
+        .iter()
+        .flatten()
+        .filter_map(|rule| rule.http.as_ref())
+        .flat_map(|http| http.paths.iter())
+        .filter_map(|path| path.backend.service.as_ref());
+    default
+        .into_iter()
+        .chain(paths)
+        .map(|service| service.name.clone())
+        .collect()
+}
+
+// Pod 通过卷、环境变量和 imagePullSecrets 引用的对象
+fn pod_references(pod: &Pod) -> Vec<(&'static str, String)> {
+    let Some(spec) = pod.spec.as_ref() else {
+        return Vec::new();
+    };
+    let mut references = Vec::new();
+    for volume in spec.volumes.iter().flatten() {
+        if let Some(config_map) = &volume.config_map {
+            references.push(("ConfigMap", config_map.name.clone()));
+        }
+        if let Some(name) = volume
+            .secret
+            .as_ref()
+            .and_then(|secret| secret.secret_name.clone())
+        {
+            references.push(("Secret", name));
+        }
+        if let Some(claim) = &volume.persistent_volume_claim {
+            references.push(("PersistentVolumeClaim", claim.claim_name.clone()));
+        }
+        for source in volume
+            .projected
+            .iter()
+            .flat_map(|projected| projected.sources.iter().flatten())
+        {
+            if let Some(config_map) = &source.config_map {
+                references.push(("ConfigMap", config_map.name.clone()));
+            }
+            if let Some(secret) = &source.secret {
+                references.push(("Secret", secret.name.clone()));
+            }
+        }
+    }
+    let containers = spec
+        .init_containers
+        .iter()
+        .flatten()
+        .chain(spec.containers.iter());
+    for container in containers {
+        for env_from in container.env_from.iter().flatten() {
+            if let Some(config_map) = &env_from.config_map_ref {
+                references.push(("ConfigMap", config_map.name.clone()));
+            }
+            if let Some(secret) = &env_from.secret_ref {
+                references.push(("Secret", secret.name.clone()));
+            }
+        }
+        for value_from in container
+            .env
+            .iter()
+            .flatten()
+            .filter_map(|env| env.value_from.as_ref())
+        {
+            if let Some(key) = &value_from.config_map_key_ref {
+                references.push(("ConfigMap", key.name.clone()));
+            }
+            if let Some(key) = &value_from.secret_key_ref {
+                references.push(("Secret", key.name.clone()));
+            }
+        }
+    }
+    for secret in spec.image_pull_secrets.iter().flatten() {
+        references.push(("Secret", secret.name.clone()));
+    }
+    references.sort();
+    references.dedup();
+    references
+}
+
+fn service_account(pod: &Pod) -> String {
+    pod.spec
+        .as_ref()
+        .and_then(|spec| spec.service_account_name.clone())
+        .unwrap_or_else(|| "default".to_string())
+}
+
+struct GraphBuilder<'a> {
+    snapshot: &'a Snapshot,
+    namespace: &'a str,
+    nodes: Vec<GraphNode>,
+    node_ids: HashSet<String>,
+    edges: Vec<GraphEdge>,
+    edge_keys: HashSet<(String, String, GraphRelation)>,
+}
+
+impl<'a> GraphBuilder<'a> {
+    fn add_node(
+        &mut self,
+        kind: &str,
+        meta: Option<&ObjectMeta>,
+        name: &str,
+        missing: bool,
+    ) -> String {
+        let id = node_id(kind, Some(self.namespace), name);
+        if self.node_ids.insert(id.clone()) {
+            self.nodes.push(GraphNode {
+                id: id.clone(),
+                kind: kind.to_string(),
+                namespace: Some(self.namespace.to_string()),
+                name: name.to_string(),
+                uid: meta.and_then(|meta| meta.uid.clone()),
+                root: false,
+                missing,
+            });
+        }
+        id
+    }
+
+    fn add_edge(&mut self, from: &str, to: &str, relation: GraphRelation) {
+        if self
+            .edge_keys
+            .insert((from.to_string(), to.to_string(), relation))
+        {
+            self.edges.push(GraphEdge {
+                from: from.to_string(),
+                to: to.to_string(),
+                relation,
+            });
+        }
+    }
+
+    // 沿 ownerReferences 向上，不在快照中的 owner 只根据引用信息添加节点
+    fn add_owners(&mut self, id: &str, owners: &[OwnerReference]) {
+        for owner in owners {
+            let owner_id = match self.snapshot.object_by_uid(&owner.uid) {
+                Some((kind, meta)) => {
+                    let name = meta.name.clone().unwrap_or_default();
+                    // 已添加过的 owner 其上游也已处理
+                    let seen = self
+                        .node_ids
+                        .contains(&node_id(kind, Some(self.namespace), &name));
+                    let owner_id = self.add_node(kind, Some(meta), &name, false);
+                    if !seen {
+                        self.add_owners(
+                            &owner_id,
+                            meta.owner_references.as_deref().unwrap_or_default(),
+                        );
+                    }
+                    owner_id
+                }
+                None => self.add_node(&owner.kind, None, &owner.name, false),
+            };
+            self.add_edge(&owner_id, id, GraphRelation::Owns);
+        }
+    }
+
+    // 沿 ownerReferences 向下，后代的 uid 记录到 visited
+    fn add_owned(&mut self, id: &str, uid: &str, visited: &mut HashSet<String>) {
+        let snapshot = self.snapshot;
+        for (kind, meta) in snapshot.owned_by(uid) {
+            let Some(child_uid) = meta.uid.clone() else {
+                continue;
+            };
+            let child_id = self.add_node(
+                kind,
+                Some(meta),
+                &meta.name.clone().unwrap_or_default(),
+                false,
+            );
+            self.add_edge(id, &child_id, GraphRelation::Owns);
+            if visited.insert(child_uid.clone()) {
+                self.add_owned(&child_id, &child_uid, visited);
+            }
+        }
+    }
+
+    fn add_referenced(
+        &mut self,
+        pod_id: &str,
+        kind: &'static str,
+        name: &str,
+        relation: GraphRelation,
+    ) {
+        let uid = self
+            .snapshot
+            .referenced
+            .get(kind)
+            .and_then(|names| names.get(name));
+        let meta = uid.map(|uid| ObjectMeta {
+            uid: uid.clone(),
+            ..ObjectMeta::default()
+        });
+        let id = self.add_node(kind, meta.as_ref(), name, uid.is_none());
+        self.add_edge(pod_id, &id, relation);
+    }
+
+    // Pod 的 Service、Ingress、挂载对象和 ServiceAccount
+    fn add_pod_links(&mut self, pod: &Pod) {
+        let snapshot = self.snapshot;
+        let pod_id = self.add_node("Pod", Some(&pod.metadata), &pod.name_any(), false);
+        self.add_owners(&pod_id, pod.owner_references());
+        for service in snapshot
+            .services
+            .iter()
+            .filter(|service| selects(service, pod))
+        {
+            let service_id = self.add_node(
+                "Service",
+                Some(&service.metadata),
+                &service.name_any(),
+                false,
+            );
+            self.add_edge(&service_id, &pod_id, GraphRelation::Selects);
+            for ingress in snapshot
+                .ingresses
+                .iter()
+                .filter(|ingress| ingress_services(ingress).contains(&service.name_any()))
+            {
+                let ingress_id = self.add_node(
+                    "Ingress",
+                    Some(&ingress.metadata),
+                    &ingress.name_any(),
+                    false,
+                );
+                self.add_edge(&ingress_id, &service_id, GraphRelation::Routes);
+            }
+        }
+        for (kind, name) in pod_references(pod) {
+            self.add_referenced(&pod_id, kind, &name, GraphRelation::Mounts);
+        }
+        self.add_referenced(
+            &pod_id,
+            "ServiceAccount",
+            &service_account(pod),
+            GraphRelation::Uses,
+        );
+    }
+}
+
+/// 构建对象的关系图：ownerReferences 上下游、选中 Pod 的 Service、路由到 Service 的 Ingress，
+/// 以及 Pod 引用的 ConfigMap/Secret/PVC/ServiceAccount
+pub async fn relationships(
+    client: Client,
+    cache: &GraphCache,
+    resource: &ApiResource,
+    capabilities: &ApiCapabilities,
+    namespace: Option<&str>,
+    name: &str,
+) -> Result<ResourceGraph, MyError> {
+    let root = dynamic_api(client.clone(), resource, capabilities, namespace)
+        .get_metadata(name)
+        .await?;
+    let Some(namespace) = root.namespace() else {
+        // 集群级对象只返回自身
+        return Ok(ResourceGraph {
+            nodes: vec![GraphNode {
+                id: node_id(&resource.kind, None, name),
+                kind: resource.kind.clone(),
+                namespace: None,
+                name: name.to_string(),
+                uid: root.uid(),
+                root: true,
+                missing: false,
+            }],
+            edges: Vec::new(),
+        });
+    };
+    let snapshot = Snapshot::load(&cache.namespace(client, &namespace)).await;
+    let mut builder = GraphBuilder {
+        snapshot: &snapshot,
+        namespace: &namespace,
+        nodes: Vec::new(),
+        node_ids: HashSet::new(),
+        edges: Vec::new(),
+        edge_keys: HashSet::new(),
+    };
+
+    let root_id = builder.add_node(&resource.kind, Some(&root.metadata), name, false);
+    builder.add_owners(&root_id, root.owner_references());
+    let root_uid = root.uid().unwrap_or_default();
+    let mut visited = HashSet::from([root_uid.clone()]);
+    builder.add_owned(&root_id, &root_uid, &mut visited);
+
+    // 关系图围绕 Pod 展开：自身或后代中的 Pod，或者反向引用根对象的 Pod
+    let pods: Vec<&Pod> = snapshot
+        .pods
+        .iter()
+        .map(|pod| pod.as_ref())
+        .filter(|pod| {
+            let in_tree = pod.uid().is_some_and(|uid| visited.contains(&uid));
+            in_tree
+                || match resource.kind.as_str() {
+                    "Service" => snapshot
+                        .services
+                        .iter()
+                        .any(|service| service.name_any() == name && selects(service, pod)),
+                    "Ingress" => snapshot
+                        .ingresses
+                        .iter()
+                        .filter(|ingress| ingress.name_any() == name)
+                        .flat_map(|ingress| ingress_services(ingress))
+                        .any(|service_name| {
+                            snapshot.services.iter().any(|service| {
+                                service.name_any() == service_name && selects(service, pod)
+                            })
+                        }),
+                    "ServiceAccount" => service_account(pod) == name,
+                    "ConfigMap" | "Secret" | "PersistentVolumeClaim" => pod_references(pod)
+                        .iter()
+                        .any(|(kind, reference)| *kind == resource.kind && reference == name),
+                    _ => false,
+                }
+        })
+        .collect();
+    for pod in pods {
+        builder.add_pod_links(pod);
+    }
+
+    let mut nodes = builder.nodes;
+    if let Some(node) = nodes.iter_mut().find(|node| node.id == root_id) {
+        node.root = true;
+    }
+    Ok(ResourceGraph {
+        nodes,
+        edges: builder.edges,
+    })
+}
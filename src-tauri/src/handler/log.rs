@@ -0,0 +1,213 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use flate2::{write::GzEncoder, Compression};
+use futures::AsyncReadExt;
+use k8s_openapi::api::core::v1::Pod;
+use kube::api::{Api, LogParams};
+use serde::Deserialize;
+use tauri::{AppHandle, Emitter};
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+use crate::{
+    error::MyError,
+    resource::log::{LogExportProgress, LogExportResult},
+};
+
+const PROGRESS_EVENT: &str = "log_export_progress";
+const PROGRESS_INTERVAL: u64 = 256 * 1024;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogExportFormat {
+    Text,
+    Gzip,
+    Zip,
+}
+
+#[derive(Debug, Clone)]
+pub struct LogSource {
+    pub pod: String,
+    pub container: String,
+}
+
+enum LogSink {
+    Text(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+    Zip(ZipWriter<BufWriter<File>>),
+}
+
+impl LogSink {
+    fn create(path: &Path, format: &LogExportFormat) -> Result<Self, MyError> {
+        let file = BufWriter::new(File::create(path)?);
+        Ok(match format {
+            LogExportFormat::Text => LogSink::Text(file),
+            LogExportFormat::Gzip => LogSink::Gzip(GzEncoder::new(file, Compression::default())),
+            LogExportFormat::Zip => LogSink::Zip(ZipWriter::new(file)),
+        })
+    }
+
+    // 文本和 gzip 格式下多个容器写入同一个文件，用标题行分隔；zip 每个容器一个文件
+    fn begin(&mut self, source: &LogSource, multiple: bool) -> Result<(), MyError> {
+        match self {
+            LogSink::Zip(zip) => {
+                zip.start_file(
+                    format!("{}/{}.log", source.pod, source.container),
+                    SimpleFileOptions::default(),
+                )?;
+            }
+            _ if multiple => {
+                let header = format!("==> {}/{} <==\n", source.pod, source.container);
+                self.writer().write_all(header.as_bytes())?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            LogSink::Text(file) => file,
+            LogSink::Gzip(gzip) => gzip,
+            LogSink::Zip(zip) => zip,
+        }
+    }
+
+    fn finish(self) -> Result<(), MyError> {
+        match self {
+            LogSink::Text(mut file) => file.flush()?,
+            LogSink::Gzip(gzip) => gzip.finish()?.flush()?,
+            LogSink::Zip(zip) => zip.finish()?.flush()?,
+        }
+        Ok(())
+    }
+}
+
+async fn copy_source(
+    pods: &Api<Pod>,
+    source: &LogSource,
+    params: &LogParams,
+    sink: &mut LogSink,
+    mut on_progress: impl FnMut(u64),
+) -> Result<u64, MyError> {
+    let lp = LogParams {
+        container: Some(source.container.clone()),
+        follow: false,
+        ..params.clone()
+    };
+    let logs = pods.log_stream(&source.pod, &lp).await?;
+    let mut logs = std::pin::pin!(logs);
+
+    let mut buffer = [0u8; 8192];
+    let mut written = 0u64;
+    let mut reported = 0u64;
+    loop {
+        let n = logs.read(&mut buffer).await?;
+        if n == 0 {
+            break;
+        }
+        sink.writer().write_all(&buffer[..n])?;
+        written += n as u64;
+        if written - reported >= PROGRESS_INTERVAL {
+            reported = written;
+            on_progress(written);
+        }
+    }
+    Ok(written)
+}
+
+/// 将日志直接写入本地文件，进度通过 log_export_progress 事件上报，日志内容不经过 webview
+pub async fn export_logs(
+    app: &AppHandle,
+    pods: Api<Pod>,
+    sources: Vec<LogSource>,
+    params: LogParams,
+    path: &Path,
+    format: LogExportFormat,
+) -> Result<LogExportResult, MyError> {
+    let multiple = sources.len() > 1 || matches!(format, LogExportFormat::Zip);
+    let sink = LogSink::create(path, &format)?;
+    let result = write_sources(app, pods, sources, params, path, sink, multiple).await;
+    // 失败时删除写了一半的文件
+    if result.is_err() {
+        if let Err(e) = std::fs::remove_file(path) {
+            tracing::warn!("Failed to remove {}: {}", path.display(), e);
+        }
+    }
+    result
+}
+
+async fn write_sources(
+    app: &AppHandle,
+    pods: Api<Pod>,
+    sources: Vec<LogSource>,
+    params: LogParams,
+    path: &Path,
+    mut sink: LogSink,
+    multiple: bool,
+) -> Result<LogExportResult, MyError> {
+    let path_str = path.to_string_lossy().to_string();
+    let total = sources.len();
+    let mut bytes = 0u64;
+    let mut failed = Vec::new();
+
+    let emit =
+        |index: usize, source: &LogSource, bytes: u64, finished: bool, error: Option<String>| {
+            let progress = LogExportProgress {
+                path: path_str.clone(),
+                pod: source.pod.clone(),
+                container: source.container.clone(),
+                index,
+                total,
+                bytes,
+                finished,
+                error,
+            };
+            if let Err(e) = app.emit(PROGRESS_EVENT, progress) {
+                tracing::warn!("Failed to emit log export progress: {}", e);
+            }
+        };
+
+    for (index, source) in sources.iter().enumerate() {
+        sink.begin(source, multiple)?;
+        let result = copy_source(&pods, source, &params, &mut sink, |written| {
+            emit(index, source, written, false, None)
+        })
+        .await;
+        match result {
+            Ok(written) => {
+                bytes += written;
+                emit(index, source, written, true, None);
+            }
+            // 聚合导出时单个容器失败（例如没有 previous 日志）不影响其他容器
+            Err(e) if total > 1 => {
+                tracing::warn!(
+                    "Failed to export logs of {}/{}: {}",
+                    source.pod,
+                    source.container,
+                    e
+                );
+                failed.push(format!("{}/{}", source.pod, source.container));
+                emit(index, source, 0, true, Some(e.to_string()));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    if total > 1 && failed.len() == total {
+        return Err(MyError::OtherError(format!(
+            "Failed to export logs of all {} containers",
+            total
+        )));
+    }
+    sink.finish()?;
+
+    Ok(LogExportResult {
+        path: path_str,
+        bytes,
+        sources: total,
+        failed,
+    })
+}
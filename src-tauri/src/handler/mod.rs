@@ -1 +1,17 @@
 pub mod cluster;
+pub mod copy;
+pub mod delete;
+pub mod diagnostics;
+pub mod file_browser;
+pub mod finalizer;
+pub mod graph;
+pub mod log;
+pub mod namespace_sleep;
+pub mod node_shell;
+pub mod port_forward;
+pub mod port_forward_profile;
+pub mod recording;
+pub mod rollout;
+pub mod scale;
+pub mod terminal;
+pub mod workload;
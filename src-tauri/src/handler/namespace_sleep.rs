@@ -0,0 +1,137 @@
+use k8s_openapi::api::{batch::v1::CronJob, core::v1::Namespace};
+use kube::{
+    api::{Api, ListParams, Patch, PatchParams},
+    Client, ResourceExt,
+};
+
+use crate::{
+    error::MyError,
+    handler::scale,
+    resource::namespace_sleep::{NamespaceSleepResult, NamespaceSleepState},
+};
+
+pub const SLEEP_STATE_ANNOTATION: &str = "ksm/sleep-state";
+
+fn is_not_found(e: &kube::Error) -> bool {
+    matches!(e, kube::Error::Api(response) if response.code == 404)
+}
+
+async fn set_sleep_state(
+    client: Client,
+    namespace: &str,
+    state: Option<&NamespaceSleepState>,
+) -> Result<(), MyError> {
+    let value = state
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| MyError::OtherError(e.to_string()))?;
+    let patch = serde_json::json!({
+        "metadata": { "annotations": { SLEEP_STATE_ANNOTATION: value } }
+    });
+    Api::<Namespace>::all(client)
+        .patch(namespace, &PatchParams::default(), &Patch::Merge(&patch))
+        .await?;
+    Ok(())
+}
+
+/// 读取命名空间的休眠状态，未休眠时为空
+pub async fn sleep_state(
+    client: Client,
+    namespace: &str,
+) -> Result<Option<NamespaceSleepState>, MyError> {
+    let namespace = Api::<Namespace>::all(client).get(namespace).await?;
+    namespace
+        .annotations()
+        .get(SLEEP_STATE_ANNOTATION)
+        .map(|state| serde_json::from_str(state).map_err(|e| MyError::OtherError(e.to_string())))
+        .transpose()
+}
+
+async fn set_suspend(api: &Api<CronJob>, name: &str, suspend: bool) -> Result<(), kube::Error> {
+    let patch = serde_json::json!({ "spec": { "suspend": suspend } });
+    api.patch(name, &PatchParams::default(), &Patch::Merge(&patch))
+        .await?;
+    Ok(())
+}
+
+/// 记录需要缩容的工作负载和 CronJob 原来的 suspend 后，工作负载按缩容到 0 的方式处理，CronJob 全部暂停
+pub async fn sleep(client: Client, namespace: &str) -> Result<NamespaceSleepResult, MyError> {
+    if sleep_state(client.clone(), namespace).await?.is_some() {
+        return Err(MyError::OtherError(format!(
+            "Namespace {} is already asleep",
+            namespace
+        )));
+    }
+
+    let mut state = NamespaceSleepState::default();
+    // 只记录本次缩容的工作负载，之前已经是 0 的保持不变
+    let workloads = scale::running_workloads(client.clone(), namespace).await?;
+    for (resource, name, _) in &workloads {
+        state
+            .workloads
+            .entry(resource.kind.clone())
+            .or_default()
+            .push(name.clone());
+    }
+    let cronjobs = Api::<CronJob>::namespaced(client.clone(), namespace);
+    for cronjob in cronjobs.list(&ListParams::default()).await?.items {
+        let suspended = cronjob
+            .spec
+            .as_ref()
+            .and_then(|spec| spec.suspend)
+            .unwrap_or(false);
+        state.cronjobs.insert(cronjob.name_any(), suspended);
+    }
+    // 先保存状态，中途失败时仍能唤醒已处理的资源
+    set_sleep_state(client.clone(), namespace, Some(&state)).await?;
+
+    let mut result = NamespaceSleepResult {
+        namespace: namespace.to_string(),
+        workloads: Vec::new(),
+        cronjobs: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for (resource, name, replicas) in &workloads {
+        result.workloads.push(
+            scale::scale_to_zero(client.clone(), namespace, resource, name, *replicas).await?,
+        );
+    }
+    for (name, _) in state.cronjobs.iter().filter(|(_, suspended)| !**suspended) {
+        set_suspend(&cronjobs, name, true).await?;
+        result.cronjobs.push(name.clone());
+    }
+    Ok(result)
+}
+
+/// 只恢复休眠时缩容的工作负载和 CronJob 的 suspend，然后删除命名空间注解
+pub async fn wake(client: Client, namespace: &str) -> Result<NamespaceSleepResult, MyError> {
+    let state = sleep_state(client.clone(), namespace)
+        .await?
+        .ok_or_else(|| MyError::OtherError(format!("Namespace {} is not asleep", namespace)))?;
+
+    let mut result = NamespaceSleepResult {
+        namespace: namespace.to_string(),
+        workloads: Vec::new(),
+        cronjobs: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for resource in scale::namespace_workloads() {
+        for name in state.workloads.get(&resource.kind).into_iter().flatten() {
+            match scale::restore(client.clone(), namespace, &resource, name).await? {
+                Some(restored) => result.workloads.push(restored),
+                None => result.skipped.push(format!("{}/{}", resource.kind, name)),
+            }
+        }
+    }
+    let cronjobs = Api::<CronJob>::namespaced(client.clone(), namespace);
+    for (name, suspended) in &state.cronjobs {
+        match set_suspend(&cronjobs, name, *suspended).await {
+            Ok(()) if !suspended => result.cronjobs.push(name.clone()),
+            Ok(()) => {}
+            Err(e) if is_not_found(&e) => result.skipped.push(format!("CronJob/{}", name)),
+            Err(e) => return Err(e.into()),
+        }
+    }
+    set_sleep_state(client, namespace, None).await?;
+    Ok(result)
+}
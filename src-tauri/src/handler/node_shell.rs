@@ -0,0 +1,89 @@
+use std::sync::Mutex;
+
+use k8s_openapi::api::core::v1::Pod;
+use kube::{
+    api::{Api, DeleteParams},
+    Client,
+};
+use tauri::Manager;
+
+use crate::{boot::setup::AppData, error::MyError};
+
+pub const DEFAULT_NODE_SHELL_IMAGE: &str = "busybox:1.36";
+pub const DEFAULT_NODE_SHELL_NAMESPACE: &str = "default";
+// 应用异常退出没能清理时，pod 最多存活的时间
+const NODE_SHELL_DEADLINE_SECONDS: i64 = 4 * 60 * 60;
+
+// 通过 nsenter 进入宿主机 1 号进程的全部命名空间
+pub fn nsenter_command(shell: &[String]) -> Vec<String> {
+    let mut command: Vec<String> = ["nsenter", "-t", "1", "-m", "-u", "-i", "-n", "-p", "--"]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+    command.extend_from_slice(shell);
+    command
+}
+
+// 固定在节点上的特权 pod，容器只负责保活，终端通过 exec nsenter 进入宿主机
+pub fn node_shell_pod(name: &str, node: &str, image: &str) -> Result<Pod, MyError> {
+    let pod = serde_json::json!({
+        "apiVersion": "v1",
+        "kind": "Pod",
+        "metadata": {
+            "name": name,
+            "labels": {
+                "app.kubernetes.io/managed-by": "ksm",
+                "ksm/node-shell": "true",
+            },
+        },
+        "spec": {
+            "nodeName": node,
+            "hostPID": true,
+            "hostNetwork": true,
+            "hostIPC": true,
+            "restartPolicy": "Never",
+            "terminationGracePeriodSeconds": 0,
+            "activeDeadlineSeconds": NODE_SHELL_DEADLINE_SECONDS,
+            "tolerations": [{ "operator": "Exists" }],
+            "containers": [{
+                "name": "shell",
+                "image": image,
+                "command": ["sleep", NODE_SHELL_DEADLINE_SECONDS.to_string()],
+                "securityContext": { "privileged": true },
+            }],
+        },
+    });
+    serde_json::from_value(pod).map_err(|e| MyError::OtherError(e.to_string()))
+}
+
+pub async fn delete_node_shell(client: Client, namespace: &str, name: &str) {
+    let pods: Api<Pod> = Api::namespaced(client, namespace);
+    if let Err(e) = pods
+        .delete(name, &DeleteParams::default().grace_period(0))
+        .await
+    {
+        tracing::warn!(
+            "Failed to delete node shell pod {}/{}: {}",
+            namespace,
+            name,
+            e
+        );
+    }
+}
+
+// 应用退出时删除仍然存在的 node shell pod
+pub fn cleanup_node_shells(app: &tauri::AppHandle) {
+    let node_shells = {
+        let state = app.state::<Mutex<AppData>>();
+        let mut app_data = state.lock().unwrap();
+        std::mem::take(&mut app_data.node_shells)
+    };
+    if node_shells.is_empty() {
+        return;
+    }
+    tauri::async_runtime::block_on(async move {
+        for (name, (client, namespace)) in node_shells {
+            delete_node_shell(client, &namespace, &name).await;
+        }
+    });
+}
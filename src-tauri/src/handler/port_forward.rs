@@ -0,0 +1,431 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use k8s_openapi::{
+    api::core::v1::{Pod, Service},
+    apimachinery::pkg::util::intstr::IntOrString,
+};
+use kube::{
+    api::{Api, ListParams},
+    core::Selector,
+    Client,
+};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    select,
+};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::{
+    error::MyError,
+    handler::workload::workload_selector,
+    resource::port_forward::{PortForwardInfo, PortForwardState, PortForwardStats},
+};
+
+const STATE_EVENT: &str = "port_forward_state";
+const STATS_EVENT: &str = "port_forward_stats";
+const STATS_INTERVAL: Duration = Duration::from_secs(1);
+const DEFAULT_ADDRESS: &str = "127.0.0.1";
+// pod 被替换后重新选择 pod 的重试次数和间隔
+const RECONNECT_ATTEMPTS: u32 = 5;
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortForwardSpec {
+    pub namespace: String,
+    // 为空或 Pod 时直接转发到 pod，也可以是 Service 或 Deployment 等工作负载
+    pub kind: Option<String>,
+    pub name: String,
+    // Service 时为 service 端口，其他情况为容器端口
+    pub remote_port: u16,
+    // 为空或 0 时由系统分配
+    pub local_port: Option<u16>,
+    pub address: Option<String>,
+}
+
+fn is_pod_ready(pod: &Pod) -> bool {
+    pod.metadata.deletion_timestamp.is_none()
+        && pod
+            .status
+            .as_ref()
+            .and_then(|status| status.conditions.as_ref())
+            .is_some_and(|conditions| {
+                conditions
+                    .iter()
+                    .any(|c| c.type_ == "Ready" && c.status == "True")
+            })
+}
+
+async fn ready_pod(pods: &Api<Pod>, selector: &Selector, target: &str) -> Result<Pod, MyError> {
+    pods.list(&ListParams::default().labels_from(selector))
+        .await?
+        .items
+        .into_iter()
+        .find(is_pod_ready)
+        .ok_or_else(|| MyError::KubeError(format!("No ready pod found for {}", target)))
+}
+
+fn named_port(pod: &Pod, name: &str) -> Option<u16> {
+    pod.spec
+        .iter()
+        .flat_map(|spec| spec.containers.iter())
+        .flat_map(|container| container.ports.iter().flatten())
+        .find(|port| port.name.as_deref() == Some(name))
+        .map(|port| port.container_port as u16)
+}
+
+/// 将转发目标解析为一个就绪的 pod 及其容器端口
+pub async fn resolve_target(
+    client: Client,
+    spec: &PortForwardSpec,
+) -> Result<(String, u16), MyError> {
+    let pods: Api<Pod> = Api::namespaced(client.clone(), &spec.namespace);
+    let target = format!("{}/{}", spec.namespace, spec.name);
+    let kind = spec.kind.as_deref().map(str::to_lowercase);
+    let pod = match kind.as_deref() {
+        None | Some("pod") => {
+            let pod = pods.get(&spec.name).await?;
+            if !is_pod_ready(&pod) {
+                return Err(MyError::KubeError(format!("Pod {} is not ready", target)));
+            }
+            return Ok((spec.name.clone(), spec.remote_port));
+        }
+        Some("service") => {
+            let service = Api::<Service>::namespaced(client, &spec.namespace)
+                .get(&spec.name)
+                .await?;
+            let service_spec = service.spec.unwrap_or_default();
+            let selector: Selector = service_spec
+                .selector
+                .filter(|selector| !selector.is_empty())
+                .ok_or_else(|| MyError::KubeError(format!("Service {} has no selector", target)))?
+                .into_iter()
+                .collect();
+            let service_port = service_spec
+                .ports
+                .unwrap_or_default()
+                .into_iter()
+                .find(|port| port.port == spec.remote_port as i32)
+                .ok_or_else(|| {
+                    MyError::KubeError(format!(
+                        "Service {} has no port {}",
+                        target, spec.remote_port
+                    ))
+                })?;
+            let pod = ready_pod(&pods, &selector, &target).await?;
+            let pod_port = match &service_port.target_port {
+                None => service_port.port as u16,
+                Some(IntOrString::Int(port)) => *port as u16,
+                Some(IntOrString::String(name)) => named_port(&pod, name).ok_or_else(|| {
+                    MyError::KubeError(format!("Pod has no container port named {}", name))
+                })?,
+            };
+            return Ok((pod.metadata.name.unwrap_or_default(), pod_port));
+        }
+        Some(kind) => {
+            let selector = workload_selector(client, &spec.namespace, kind, &spec.name).await?;
+            ready_pod(&pods, &selector, &target).await?
+        }
+    };
+    Ok((pod.metadata.name.unwrap_or_default(), spec.remote_port))
+}
+
+struct Forward {
+    spec: PortForwardSpec,
+    client: Client,
+    info: Mutex<PortForwardInfo>,
+    connections: AtomicUsize,
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+    cancel: CancellationToken,
+}
+
+impl Forward {
+    fn snapshot(&self) -> PortForwardInfo {
+        let mut info = self.info.lock().unwrap().clone();
+        info.connections = self.connections.load(Ordering::Relaxed);
+        info.bytes_in = self.bytes_in.load(Ordering::Relaxed);
+        info.bytes_out = self.bytes_out.load(Ordering::Relaxed);
+        info
+    }
+
+    fn stats(&self) -> PortForwardStats {
+        PortForwardStats {
+            id: self.info.lock().unwrap().id.clone(),
+            connections: self.connections.load(Ordering::Relaxed),
+            bytes_in: self.bytes_in.load(Ordering::Relaxed),
+            bytes_out: self.bytes_out.load(Ordering::Relaxed),
+        }
+    }
+
+    fn target(&self) -> (String, u16) {
+        let info = self.info.lock().unwrap();
+        (info.pod.clone(), info.pod_port)
+    }
+
+    fn set_target(&self, pod: String, pod_port: u16) {
+        let mut info = self.info.lock().unwrap();
+        info.pod = pod;
+        info.pod_port = pod_port;
+    }
+
+    // 状态变化时推送 port_forward_state 事件
+    fn set_state(&self, app: &AppHandle, state: PortForwardState, error: Option<String>) {
+        {
+            let mut info = self.info.lock().unwrap();
+            if info.state == state && info.error == error {
+                return;
+            }
+            info.state = state;
+            info.error = error;
+        }
+        emit(app, STATE_EVENT, self.snapshot());
+    }
+}
+
+fn emit<S: Serialize + Clone>(app: &AppHandle, event: &str, payload: S) {
+    if let Err(e) = app.emit(event, payload) {
+        tracing::warn!("Failed to emit {}: {}", event, e);
+    }
+}
+
+async fn copy_counted<R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    counter: &AtomicU64,
+) -> std::io::Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut buffer = [0u8; 8192];
+    loop {
+        let n = reader.read(&mut buffer).await?;
+        if n == 0 {
+            break;
+        }
+        writer.write_all(&buffer[..n]).await?;
+        counter.fetch_add(n as u64, Ordering::Relaxed);
+    }
+    writer.shutdown().await
+}
+
+// 连接当前 pod，失败时认为 pod 已被替换，重新解析目标后重试
+async fn connect(
+    app: &AppHandle,
+    forward: &Forward,
+) -> Result<(kube::api::Portforwarder, u16), MyError> {
+    let pods: Api<Pod> = Api::namespaced(forward.client.clone(), &forward.spec.namespace);
+    let mut attempt = 0;
+    loop {
+        let (pod, port) = forward.target();
+        let error = match pods.portforward(&pod, &[port]).await {
+            Ok(portforwarder) => {
+                forward.set_state(app, PortForwardState::Active, None);
+                return Ok((portforwarder, port));
+            }
+            Err(e) => e.to_string(),
+        };
+        attempt += 1;
+        if attempt > RECONNECT_ATTEMPTS {
+            forward.set_state(app, PortForwardState::Error, Some(error.clone()));
+            return Err(MyError::KubeError(error));
+        }
+        forward.set_state(app, PortForwardState::Reconnecting, Some(error));
+        select! {
+            _ = tokio::time::sleep(RECONNECT_DELAY) => {}
+            _ = forward.cancel.cancelled() => {
+                return Err(MyError::OtherError("Port forward stopped".to_string()));
+            }
+        }
+        match resolve_target(forward.client.clone(), &forward.spec).await {
+            Ok((pod, port)) => forward.set_target(pod, port),
+            Err(e) => tracing::info!("Port forward target not available yet: {}", e),
+        }
+    }
+}
+
+async fn forward_connection(
+    app: &AppHandle,
+    forward: &Forward,
+    mut tcp: TcpStream,
+) -> Result<(), MyError> {
+    let (mut portforwarder, port) = connect(app, forward).await?;
+    let upstream = portforwarder
+        .take_stream(port)
+        .ok_or_else(|| MyError::KubeError(format!("Port {} not forwarded", port)))?;
+    let mut error = portforwarder.take_error(port);
+
+    let (mut tcp_read, mut tcp_write) = tcp.split();
+    let (mut upstream_read, mut upstream_write) = tokio::io::split(upstream);
+    let upload = copy_counted(&mut tcp_read, &mut upstream_write, &forward.bytes_out);
+    let download = copy_counted(&mut upstream_read, &mut tcp_write, &forward.bytes_in);
+    let result = select! {
+        result = async { tokio::try_join!(upload, download) } => {
+            result.map(|_| ()).map_err(MyError::from)
+        }
+        Some(message) = async { error.as_mut()?.await } => Err(MyError::KubeError(message)),
+        _ = forward.cancel.cancelled() => Ok(()),
+    };
+    drop(upstream_read);
+    drop(upstream_write);
+
+    if forward.cancel.is_cancelled() || result.is_err() {
+        portforwarder.abort();
+    } else if let Err(e) = portforwarder.join().await {
+        tracing::warn!("Port forward connection closed with error: {}", e);
+    }
+    result
+}
+
+async fn accept_loop(app: AppHandle, forward: Arc<Forward>, listener: TcpListener) {
+    loop {
+        let tcp = select! {
+            _ = forward.cancel.cancelled() => break,
+            accepted = listener.accept() => match accepted {
+                Ok((tcp, _)) => tcp,
+                Err(e) => {
+                    tracing::warn!("Failed to accept port forward connection: {}", e);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            },
+        };
+        let app = app.clone();
+        let forward = forward.clone();
+        tokio::spawn(async move {
+            forward.connections.fetch_add(1, Ordering::Relaxed);
+            if let Err(e) = forward_connection(&app, &forward, tcp).await {
+                tracing::warn!("Port forward connection failed: {}", e);
+            }
+            forward.connections.fetch_sub(1, Ordering::Relaxed);
+        });
+    }
+}
+
+// 定期推送连接数和字节计数，没有变化时不发送
+async fn report_stats(app: AppHandle, forward: Arc<Forward>) {
+    let mut interval = tokio::time::interval(STATS_INTERVAL);
+    let mut last = (0, 0, 0);
+    loop {
+        select! {
+            _ = forward.cancel.cancelled() => break,
+            _ = interval.tick() => {}
+        }
+        let stats = forward.stats();
+        let current = (stats.connections, stats.bytes_in, stats.bytes_out);
+        if current != last {
+            last = current;
+            emit(&app, STATS_EVENT, stats);
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct PortForwardManager {
+    forwards: Arc<Mutex<HashMap<String, Arc<Forward>>>>,
+}
+
+impl PortForwardManager {
+    pub async fn start(
+        &self,
+        app: AppHandle,
+        client: Client,
+        spec: PortForwardSpec,
+        profile: Option<String>,
+    ) -> Result<PortForwardInfo, MyError> {
+        let (pod, pod_port) = resolve_target(client.clone(), &spec).await?;
+        let address = spec
+            .address
+            .clone()
+            .unwrap_or_else(|| DEFAULT_ADDRESS.to_string());
+        let listener = TcpListener::bind((address.as_str(), spec.local_port.unwrap_or(0))).await?;
+        let local_port = listener.local_addr()?.port();
+
+        let info = PortForwardInfo {
+            id: Uuid::new_v4().to_string(),
+            profile,
+            namespace: spec.namespace.clone(),
+            kind: spec.kind.clone().unwrap_or_else(|| "Pod".to_string()),
+            name: spec.name.clone(),
+            address,
+            local_port,
+            remote_port: spec.remote_port,
+            pod,
+            pod_port,
+            state: PortForwardState::Active,
+            error: None,
+            connections: 0,
+            bytes_in: 0,
+            bytes_out: 0,
+        };
+        let forward = Arc::new(Forward {
+            spec,
+            client,
+            info: Mutex::new(info.clone()),
+            connections: AtomicUsize::new(0),
+            bytes_in: AtomicU64::new(0),
+            bytes_out: AtomicU64::new(0),
+            cancel: CancellationToken::new(),
+        });
+        self.forwards
+            .lock()
+            .unwrap()
+            .insert(info.id.clone(), forward.clone());
+
+        tokio::spawn(accept_loop(app.clone(), forward.clone(), listener));
+        tokio::spawn(report_stats(app.clone(), forward));
+        emit(&app, STATE_EVENT, info.clone());
+        tracing::info!(
+            "Forwarding {}:{} to {}/{}:{}",
+            info.address,
+            info.local_port,
+            info.namespace,
+            info.name,
+            info.remote_port
+        );
+        Ok(info)
+    }
+
+    pub fn stop(&self, app: &AppHandle, id: &str) -> Result<(), MyError> {
+        let forward = self
+            .forwards
+            .lock()
+            .unwrap()
+            .remove(id)
+            .ok_or_else(|| MyError::OtherError(format!("Port forward {} not found", id)))?;
+        forward.cancel.cancel();
+        forward.set_state(app, PortForwardState::Stopped, None);
+        Ok(())
+    }
+
+    pub fn is_running(&self, profile: &str) -> bool {
+        self.forwards
+            .lock()
+            .unwrap()
+            .values()
+            .any(|forward| forward.info.lock().unwrap().profile.as_deref() == Some(profile))
+    }
+
+    pub fn list(&self) -> Vec<PortForwardInfo> {
+        let mut forwards: Vec<PortForwardInfo> = self
+            .forwards
+            .lock()
+            .unwrap()
+            .values()
+            .map(|forward| forward.snapshot())
+            .collect();
+        forwards.sort_by_key(|info| info.local_port);
+        forwards
+    }
+}
@@ -0,0 +1,194 @@
+use std::{collections::BTreeMap, path::PathBuf, sync::Mutex};
+
+use kube::{
+    config::{KubeConfigOptions, Kubeconfig},
+    Client, Config,
+};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use uuid::Uuid;
+
+use crate::{
+    boot::setup::AppData,
+    error::MyError,
+    handler::port_forward::{PortForwardManager, PortForwardSpec},
+    resource::port_forward::PortForwardInfo,
+    utils,
+};
+
+const PROFILES_FILE: &str = "port_forwards.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortForwardProfile {
+    // 新建时为空，保存时生成
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    // kubeconfig 中的 context 名
+    pub context: String,
+    #[serde(flatten)]
+    pub spec: PortForwardSpec,
+    // 应用启动时自动启动
+    #[serde(default)]
+    pub start_on_launch: bool,
+    // switch_cluster 切换到该 context 时自动启动
+    #[serde(default)]
+    pub start_on_switch: bool,
+}
+
+fn profiles_path(app: &AppHandle) -> Result<PathBuf, MyError> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| MyError::IOError(e.to_string()))?;
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir.join(PROFILES_FILE))
+}
+
+pub fn load_profiles(app: &AppHandle) -> Result<Vec<PortForwardProfile>, MyError> {
+    let path = profiles_path(app)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path)?;
+    serde_json::from_str(&content)
+        .map_err(|e| MyError::OtherError(format!("Invalid {}: {}", path.display(), e)))
+}
+
+fn write_profiles(app: &AppHandle, profiles: &[PortForwardProfile]) -> Result<(), MyError> {
+    let content =
+        serde_json::to_string_pretty(profiles).map_err(|e| MyError::OtherError(e.to_string()))?;
+    std::fs::write(profiles_path(app)?, content)?;
+    Ok(())
+}
+
+// 新增或按 id 覆盖已有配置
+pub fn save_profile(
+    app: &AppHandle,
+    mut profile: PortForwardProfile,
+) -> Result<PortForwardProfile, MyError> {
+    let mut profiles = load_profiles(app)?;
+    if profile.id.is_empty() {
+        profile.id = Uuid::new_v4().to_string();
+    }
+    match profiles.iter_mut().find(|p| p.id == profile.id) {
+        Some(existing) => *existing = profile.clone(),
+        None => profiles.push(profile.clone()),
+    }
+    write_profiles(app, &profiles)?;
+    Ok(profile)
+}
+
+pub fn delete_profile(app: &AppHandle, id: &str) -> Result<(), MyError> {
+    let mut profiles = load_profiles(app)?;
+    profiles.retain(|profile| profile.id != id);
+    write_profiles(app, &profiles)
+}
+
+pub async fn context_client(kubeconfig: Kubeconfig, context: &str) -> Result<Client, MyError> {
+    let options = KubeConfigOptions {
+        context: Some(context.to_string()),
+        ..KubeConfigOptions::default()
+    };
+    let config = Config::from_custom_kubeconfig(kubeconfig, &options).await?;
+    utils::cluster::generate_client(&config)
+}
+
+// 启动一组配置，已在运行的跳过，单个失败只记录日志
+async fn start_profiles(
+    app: &AppHandle,
+    manager: &PortForwardManager,
+    client: Client,
+    profiles: Vec<PortForwardProfile>,
+) {
+    for profile in profiles {
+        if manager.is_running(&profile.id) {
+            continue;
+        }
+        if let Err(e) = manager
+            .start(
+                app.clone(),
+                client.clone(),
+                profile.spec,
+                Some(profile.id.clone()),
+            )
+            .await
+        {
+            tracing::warn!("Failed to start port forward {}: {}", profile.name, e);
+        }
+    }
+}
+
+/// 应用启动时按 context 分组启动标记了 start_on_launch 的配置
+pub async fn start_on_launch(app: AppHandle) {
+    let profiles = match load_profiles(&app) {
+        Ok(profiles) => profiles,
+        Err(e) => {
+            tracing::warn!("Failed to load port forward profiles: {}", e);
+            return;
+        }
+    };
+    let mut by_context: BTreeMap<String, Vec<PortForwardProfile>> = BTreeMap::new();
+    for profile in profiles.into_iter().filter(|p| p.start_on_launch) {
+        by_context
+            .entry(profile.context.clone())
+            .or_default()
+            .push(profile);
+    }
+    if by_context.is_empty() {
+        return;
+    }
+
+    let (kubeconfig, manager) = {
+        let state = app.state::<Mutex<AppData>>();
+        let app_data = state.lock().unwrap();
+        (
+            app_data.kubernetes_configs.clone(),
+            app_data.port_forward.clone(),
+        )
+    };
+    for (context, profiles) in by_context {
+        match context_client(kubeconfig.clone(), &context).await {
+            Ok(client) => start_profiles(&app, &manager, client, profiles).await,
+            Err(e) => tracing::warn!("Failed to connect to {}: {}", context, e),
+        }
+    }
+}
+
+/// 切换集群后启动该 context 下标记了 start_on_switch 的配置
+pub async fn start_on_switch(
+    app: &AppHandle,
+    manager: &PortForwardManager,
+    client: Client,
+    context: &str,
+) {
+    let profiles = match load_profiles(app) {
+        Ok(profiles) => profiles,
+        Err(e) => {
+            tracing::warn!("Failed to load port forward profiles: {}", e);
+            return;
+        }
+    };
+    let profiles = profiles
+        .into_iter()
+        .filter(|p| p.start_on_switch && p.context == context)
+        .collect();
+    start_profiles(app, manager, client, profiles).await;
+}
+
+/// 手动启动某个配置
+pub async fn start_profile(
+    app: &AppHandle,
+    manager: &PortForwardManager,
+    kubeconfig: Kubeconfig,
+    id: &str,
+) -> Result<PortForwardInfo, MyError> {
+    let profile = load_profiles(app)?
+        .into_iter()
+        .find(|profile| profile.id == id)
+        .ok_or_else(|| MyError::OtherError(format!("Port forward profile {} not found", id)))?;
+    let client = context_client(kubeconfig, &profile.context).await?;
+    manager
+        .start(app.clone(), client, profile.spec, Some(profile.id))
+        .await
+}
@@ -0,0 +1,194 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{boot::websocket::StdStream, error::MyError, utils::utf8::Utf8Decoder};
+
+const DEFAULT_WIDTH: u16 = 80;
+const DEFAULT_HEIGHT: u16 = 24;
+
+// asciinema v2 文件头
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CastHeader {
+    pub version: u8,
+    pub width: u16,
+    pub height: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<serde_json::Value>,
+}
+
+// 事件行：[时间(秒), 类型, 数据]，类型为 o 输出、i 输入、r 终端大小 "COLSxROWS"
+pub type CastEvent = (f64, String, String);
+
+pub fn recordings_dir() -> Result<PathBuf, MyError> {
+    let dir = dirs::data_dir()
+        .ok_or_else(|| MyError::IOError("No data directory available".to_string()))?
+        .join("ksm")
+        .join("recordings");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+// 录像 id 即文件名（不含 .cast），拒绝路径分隔符防止越出录像目录
+pub fn recording_path(id: &str) -> Result<PathBuf, MyError> {
+    if id.is_empty() || id.contains(['/', '\\']) || id.starts_with('.') {
+        return Err(MyError::OtherError(format!("Invalid recording id: {}", id)));
+    }
+    Ok(recordings_dir()?.join(format!("{}.cast", id)))
+}
+
+pub fn read_header(reader: &mut impl BufRead) -> Result<CastHeader, MyError> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let header: CastHeader = serde_json::from_str(&line)
+        .map_err(|e| MyError::OtherError(format!("Invalid recording header: {}", e)))?;
+    if header.version != 2 {
+        return Err(MyError::OtherError(format!(
+            "Unsupported asciicast version {}",
+            header.version
+        )));
+    }
+    Ok(header)
+}
+
+pub fn open_recording(id: &str) -> Result<(CastHeader, BufReader<File>), MyError> {
+    let mut reader = BufReader::new(File::open(recording_path(id)?)?);
+    let header = read_header(&mut reader)?;
+    Ok((header, reader))
+}
+
+#[derive(Debug)]
+struct RecorderInner {
+    writer: BufWriter<File>,
+    // 非 tty 会话中 stdout/stderr 交替到达，各自保留未完成的多字节字符
+    stdout: Utf8Decoder,
+    stderr: Utf8Decoder,
+    input: Utf8Decoder,
+}
+
+// 终端会话录像，stdout/stderr/stdin 任务共享同一个文件
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    id: String,
+    start: Instant,
+    inner: Arc<Mutex<RecorderInner>>,
+}
+
+impl Recorder {
+    pub fn create(
+        title: &str,
+        cols: Option<u16>,
+        rows: Option<u16>,
+        command: &[String],
+    ) -> Result<Self, MyError> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let slug: String = title
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let id = format!(
+            "{}-{}-{}",
+            timestamp,
+            slug,
+            &uuid::Uuid::new_v4().simple().to_string()[..5]
+        );
+        let header = CastHeader {
+            version: 2,
+            width: cols.unwrap_or(DEFAULT_WIDTH),
+            height: rows.unwrap_or(DEFAULT_HEIGHT),
+            timestamp: Some(timestamp),
+            title: Some(title.to_string()),
+            env: Some(serde_json::json!({
+                "SHELL": command.join(" "),
+                "TERM": "xterm-256color",
+            })),
+        };
+
+        let mut writer = BufWriter::new(File::create(recording_path(&id)?)?);
+        serde_json::to_writer(&mut writer, &header).map_err(|e| MyError::IOError(e.to_string()))?;
+        writer.write_all(b"\n")?;
+        Ok(Recorder {
+            id,
+            start: Instant::now(),
+            inner: Arc::new(Mutex::new(RecorderInner {
+                writer,
+                stdout: Utf8Decoder::default(),
+                stderr: Utf8Decoder::default(),
+                input: Utf8Decoder::default(),
+            })),
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn write_event(writer: &mut BufWriter<File>, time: f64, code: &str, data: &str) {
+        let event = serde_json::json!([time, code, data]);
+        let result = serde_json::to_writer(&mut *writer, &event)
+            .map_err(std::io::Error::from)
+            .and_then(|_| writer.write_all(b"\n"));
+        if let Err(e) = result {
+            tracing::warn!("Failed to write recording event: {}", e);
+        }
+    }
+
+    pub fn output(&self, stream: StdStream, data: &[u8]) {
+        let time = self.start.elapsed().as_secs_f64();
+        let mut inner = self.inner.lock().unwrap();
+        let text = match stream {
+            StdStream::Stderr => inner.stderr.decode(data),
+            _ => inner.stdout.decode(data),
+        };
+        if !text.is_empty() {
+            Self::write_event(&mut inner.writer, time, "o", &text);
+        }
+    }
+
+    pub fn input(&self, data: &[u8]) {
+        let time = self.start.elapsed().as_secs_f64();
+        let mut inner = self.inner.lock().unwrap();
+        let text = inner.input.decode(data);
+        if !text.is_empty() {
+            Self::write_event(&mut inner.writer, time, "i", &text);
+        }
+    }
+
+    pub fn resize(&self, cols: u16, rows: u16) {
+        let time = self.start.elapsed().as_secs_f64();
+        let mut inner = self.inner.lock().unwrap();
+        Self::write_event(&mut inner.writer, time, "r", &format!("{}x{}", cols, rows));
+    }
+
+    pub fn finish(&self) {
+        let time = self.start.elapsed().as_secs_f64();
+        let mut inner = self.inner.lock().unwrap();
+        for rest in [inner.stdout.finish(), inner.stderr.finish()] {
+            if !rest.is_empty() {
+                Self::write_event(&mut inner.writer, time, "o", &rest);
+            }
+        }
+        if let Err(e) = inner.writer.flush() {
+            tracing::warn!("Failed to flush recording {}: {}", self.id, e);
+        }
+    }
+}
@@ -0,0 +1,806 @@
+use std::{fmt::Debug, time::Duration};
+
+use futures::{stream::BoxStream, StreamExt};
+use k8s_openapi::{
+    api::apps::v1::{ControllerRevision, DaemonSet, Deployment, ReplicaSet, StatefulSet},
+    apimachinery::pkg::apis::meta::v1::{LabelSelector, ObjectMeta},
+};
+use kube::{
+    api::{Api, ListParams, Patch, PatchParams, PostParams},
+    core::Selector,
+    runtime::{wait::await_condition, watcher::watch_object, WatchStreamExt},
+    Client, Resource, ResourceExt,
+};
+use serde::de::DeserializeOwned;
+use similar::TextDiff;
+
+use crate::{
+    error::MyError,
+    resource::rollout::{RolloutPhase, RolloutProgress, RolloutResult, RolloutRevision},
+};
+
+pub const REVISION_ANNOTATION: &str = "deployment.kubernetes.io/revision";
+const POD_TEMPLATE_HASH: &str = "pod-template-hash";
+// 等待控制器处理新 generation 的时间
+const OBSERVE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RolloutKind {
+    Deployment,
+    StatefulSet,
+    DaemonSet,
+}
+
+impl RolloutKind {
+    pub fn parse(kind: &str) -> Result<Self, MyError> {
+        match kind.to_lowercase().as_str() {
+            "deployment" => Ok(RolloutKind::Deployment),
+            "statefulset" => Ok(RolloutKind::StatefulSet),
+            "daemonset" => Ok(RolloutKind::DaemonSet),
+            _ => Err(MyError::UnsupportedKind(kind.to_string())),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RolloutKind::Deployment => "Deployment",
+            RolloutKind::StatefulSet => "StatefulSet",
+            RolloutKind::DaemonSet => "DaemonSet",
+        }
+    }
+}
+
+fn owned_by(meta: &ObjectMeta, uid: &str) -> bool {
+    meta.owner_references
+        .iter()
+        .flatten()
+        .any(|owner| owner.uid == uid)
+}
+
+pub fn revision_of(meta: &ObjectMeta) -> Option<i64> {
+    meta.annotations
+        .as_ref()?
+        .get(REVISION_ANNOTATION)?
+        .parse()
+        .ok()
+}
+
+fn selector_of(selector: &LabelSelector) -> Result<Selector, MyError> {
+    Selector::try_from(selector.clone()).map_err(|e| MyError::OtherError(e.to_string()))
+}
+
+/// Deployment 管理的 ReplicaSet，按修订号从小到大排列
+pub async fn deployment_replica_sets(
+    client: Client,
+    deployment: &Deployment,
+) -> Result<Vec<(i64, ReplicaSet)>, MyError> {
+    let namespace = deployment.namespace().unwrap_or_default();
+    let uid = deployment.uid().unwrap_or_default();
+    let selector = deployment
+        .spec
+        .as_ref()
+        .map(|spec| selector_of(&spec.selector))
+        .transpose()?
+        .unwrap_or_default();
+    let mut replica_sets: Vec<(i64, ReplicaSet)> =
+        Api::<ReplicaSet>::namespaced(client, &namespace)
+            .list(&ListParams::default().labels_from(&selector))
+            .await?
+            .items
+            .into_iter()
+            .filter(|rs| owned_by(&rs.metadata, &uid))
+            .filter_map(|rs| Some((revision_of(&rs.metadata)?, rs)))
+            .collect();
+    replica_sets.sort_by_key(|(revision, _)| *revision);
+    Ok(replica_sets)
+}
+
+/// StatefulSet/DaemonSet 的 ControllerRevision，按修订号从小到大排列
+pub async fn controller_revisions(
+    client: Client,
+    meta: &ObjectMeta,
+    selector: &LabelSelector,
+) -> Result<Vec<ControllerRevision>, MyError> {
+    let namespace = meta.namespace.clone().unwrap_or_default();
+    let uid = meta.uid.clone().unwrap_or_default();
+    let mut revisions: Vec<ControllerRevision> =
+        Api::<ControllerRevision>::namespaced(client, &namespace)
+            .list(&ListParams::default().labels_from(&selector_of(selector)?))
+            .await?
+            .items
+            .into_iter()
+            .filter(|revision| owned_by(&revision.metadata, &uid))
+            .collect();
+    revisions.sort_by_key(|revision| revision.revision);
+    Ok(revisions)
+}
+
+fn generation_observed(meta: &ObjectMeta, observed: Option<i64>) -> bool {
+    observed.is_some() && observed >= meta.generation
+}
+
+// 等待控制器观察到最新的 generation，超时只记录日志
+async fn wait_observed<K>(api: Api<K>, name: &str, observed: fn(&K) -> bool)
+where
+    K: Resource + Clone + Debug + DeserializeOwned + Send + 'static,
+{
+    let condition = move |obj: Option<&K>| obj.is_some_and(observed);
+    if tokio::time::timeout(OBSERVE_TIMEOUT, await_condition(api, name, condition))
+        .await
+        .is_err()
+    {
+        tracing::info!("Timed out waiting for {} to be observed", name);
+    }
+}
+
+fn deployment_observed(deployment: &Deployment) -> bool {
+    generation_observed(
+        &deployment.metadata,
+        deployment
+            .status
+            .as_ref()
+            .and_then(|s| s.observed_generation),
+    )
+}
+
+fn statefulset_observed(statefulset: &StatefulSet) -> bool {
+    generation_observed(
+        &statefulset.metadata,
+        statefulset
+            .status
+            .as_ref()
+            .and_then(|s| s.observed_generation),
+    )
+}
+
+fn daemonset_observed(daemonset: &DaemonSet) -> bool {
+    generation_observed(
+        &daemonset.metadata,
+        daemonset
+            .status
+            .as_ref()
+            .and_then(|s| s.observed_generation),
+    )
+}
+
+/// 读取工作负载当前的修订号和暂停状态
+pub async fn rollout_result(
+    client: Client,
+    namespace: &str,
+    kind: RolloutKind,
+    name: &str,
+) -> Result<RolloutResult, MyError> {
+    let (revision, paused) = match kind {
+        RolloutKind::Deployment => {
+            let deployment = Api::<Deployment>::namespaced(client, namespace)
+                .get(name)
+                .await?;
+            let paused = deployment
+                .spec
+                .as_ref()
+                .and_then(|spec| spec.paused)
+                .unwrap_or(false);
+            (revision_of(&deployment.metadata), paused)
+        }
+        RolloutKind::StatefulSet => {
+            let statefulset = Api::<StatefulSet>::namespaced(client.clone(), namespace)
+                .get(name)
+                .await?;
+            let revision = match statefulset
+                .status
+                .as_ref()
+                .and_then(|status| status.update_revision.as_deref())
+            {
+                Some(update_revision) => Some(
+                    Api::<ControllerRevision>::namespaced(client, namespace)
+                        .get(update_revision)
+                        .await?
+                        .revision,
+                ),
+                None => None,
+            };
+            (revision, false)
+        }
+        RolloutKind::DaemonSet => {
+            let daemonset = Api::<DaemonSet>::namespaced(client.clone(), namespace)
+                .get(name)
+                .await?;
+            let selector = daemonset
+                .spec
+                .as_ref()
+                .map(|spec| spec.selector.clone())
+                .unwrap_or_default();
+            let revision = controller_revisions(client, &daemonset.metadata, &selector)
+                .await?
+                .last()
+                .map(|revision| revision.revision);
+            (revision, false)
+        }
+    };
+    Ok(RolloutResult {
+        kind: kind.as_str().to_string(),
+        namespace: namespace.to_string(),
+        name: name.to_string(),
+        revision,
+        paused,
+    })
+}
+
+/// 修改 pod 模板的 restartedAt 注解触发滚动重启，相当于 kubectl rollout restart
+pub async fn restart(
+    client: Client,
+    namespace: &str,
+    kind: RolloutKind,
+    name: &str,
+) -> Result<RolloutResult, MyError> {
+    match kind {
+        RolloutKind::Deployment => {
+            let api = Api::<Deployment>::namespaced(client.clone(), namespace);
+            api.restart(name).await?;
+            wait_observed(api, name, deployment_observed).await;
+        }
+        RolloutKind::StatefulSet => {
+            let api = Api::<StatefulSet>::namespaced(client.clone(), namespace);
+            api.restart(name).await?;
+            wait_observed(api, name, statefulset_observed).await;
+        }
+        RolloutKind::DaemonSet => {
+            let api = Api::<DaemonSet>::namespaced(client.clone(), namespace);
+            api.restart(name).await?;
+            wait_observed(api, name, daemonset_observed).await;
+        }
+    }
+    rollout_result(client, namespace, kind, name).await
+}
+
+/// 暂停或恢复 Deployment 的滚动更新
+pub async fn set_paused(
+    client: Client,
+    namespace: &str,
+    name: &str,
+    paused: bool,
+) -> Result<RolloutResult, MyError> {
+    let api = Api::<Deployment>::namespaced(client.clone(), namespace);
+    let patch = serde_json::json!({ "spec": { "paused": paused } });
+    api.patch(name, &PatchParams::default(), &Patch::Merge(&patch))
+        .await?;
+    wait_observed(api, name, deployment_observed).await;
+    rollout_result(client, namespace, RolloutKind::Deployment, name).await
+}
+
+/// 把 Deployment 的 pod 模板回滚到指定修订号对应 ReplicaSet 的模板，未指定时回滚到上一个修订
+pub async fn undo(
+    client: Client,
+    namespace: &str,
+    name: &str,
+    to_revision: Option<i64>,
+) -> Result<RolloutResult, MyError> {
+    let api = Api::<Deployment>::namespaced(client.clone(), namespace);
+    let mut deployment = api.get(name).await?;
+    if deployment
+        .spec
+        .as_ref()
+        .and_then(|spec| spec.paused)
+        .unwrap_or(false)
+    {
+        return Err(MyError::OtherError(format!(
+            "Deployment {} is paused, resume it before undo",
+            name
+        )));
+    }
+
+    let current = revision_of(&deployment.metadata);
+    let replica_sets = deployment_replica_sets(client.clone(), &deployment).await?;
+    let target = match to_revision {
+        Some(revision) => replica_sets.iter().find(|(r, _)| *r == revision),
+        None => replica_sets.iter().rev().find(|(r, _)| Some(*r) != current),
+    };
+    let (revision, replica_set) = target.ok_or_else(|| {
+        MyError::OtherError(match to_revision {
+            Some(revision) => format!("Revision {} not found for {}", revision, name),
+            None => format!("No previous revision found for {}", name),
+        })
+    })?;
+    if Some(*revision) == current {
+        return Err(MyError::OtherError(format!(
+            "Deployment {} is already at revision {}",
+            name, revision
+        )));
+    }
+
+    let mut template = replica_set
+        .spec
+        .as_ref()
+        .and_then(|spec| spec.template.clone())
+        .ok_or_else(|| MyError::OtherError(format!("Revision {} has no template", revision)))?;
+    // pod-template-hash 由 ReplicaSet 控制器添加，不属于 Deployment 模板
+    if let Some(labels) = template
+        .metadata
+        .as_mut()
+        .and_then(|meta| meta.labels.as_mut())
+    {
+        labels.remove(POD_TEMPLATE_HASH);
+    }
+    if let Some(spec) = deployment.spec.as_mut() {
+        spec.template = template;
+    }
+    // replace 带 resourceVersion，期间被修改时返回冲突而不是覆盖
+    api.replace(name, &PostParams::default(), &deployment)
+        .await?;
+    wait_observed(api, name, deployment_observed).await;
+    rollout_result(client, namespace, RolloutKind::Deployment, name).await
+}
+
+struct StatusCounts {
+    replicas: i32,
+    updated: i32,
+    ready: i32,
+    available: i32,
+}
+
+fn progress(
+    kind: RolloutKind,
+    meta: &ObjectMeta,
+    phase: RolloutPhase,
+    message: String,
+    counts: StatusCounts,
+    deadline_exceeded: bool,
+) -> RolloutProgress {
+    RolloutProgress {
+        kind: kind.as_str().to_string(),
+        namespace: meta.namespace.clone().unwrap_or_default(),
+        name: meta.name.clone().unwrap_or_default(),
+        phase,
+        message,
+        replicas: counts.replicas,
+        updated_replicas: counts.updated,
+        ready_replicas: counts.ready,
+        available_replicas: counts.available,
+        deadline_exceeded,
+    }
+}
+
+// 与 kubectl 的 DeploymentStatusViewer 判断一致
+fn deployment_progress(deployment: &Deployment) -> RolloutProgress {
+    let name = deployment.name_any();
+    let status = deployment.status.clone().unwrap_or_default();
+    let replicas = deployment
+        .spec
+        .as_ref()
+        .and_then(|spec| spec.replicas)
+        .unwrap_or(1);
+    let counts = StatusCounts {
+        replicas,
+        updated: status.updated_replicas.unwrap_or(0),
+        ready: status.ready_replicas.unwrap_or(0),
+        available: status.available_replicas.unwrap_or(0),
+    };
+    let deadline_exceeded = status.conditions.iter().flatten().any(|condition| {
+        condition.type_ == "Progressing"
+            && condition.reason.as_deref() == Some("ProgressDeadlineExceeded")
+    });
+    let (phase, message) = if !deployment_observed(deployment) {
+        (
+            RolloutPhase::Progressing,
+            "Waiting for deployment spec update to be observed".to_string(),
+        )
+    } else if deadline_exceeded {
+        (
+            RolloutPhase::Failed,
+            format!("deployment {:?} exceeded its progress deadline", name),
+        )
+    } else if counts.updated < replicas {
+        (
+            RolloutPhase::Progressing,
+            format!(
+                "Waiting for deployment {:?} rollout to finish: {} out of {} new replicas have been updated",
+                name, counts.updated, replicas
+            ),
+        )
+    } else if status.replicas.unwrap_or(0) > counts.updated {
+        (
+            RolloutPhase::Progressing,
+            format!(
+                "Waiting for deployment {:?} rollout to finish: {} old replicas are pending termination",
+                name,
+                status.replicas.unwrap_or(0) - counts.updated
+            ),
+        )
+    } else if counts.available < counts.updated {
+        (
+            RolloutPhase::Progressing,
+            format!(
+                "Waiting for deployment {:?} rollout to finish: {} of {} updated replicas are available",
+                name, counts.available, counts.updated
+            ),
+        )
+    } else {
+        (
+            RolloutPhase::Complete,
+            format!("deployment {:?} successfully rolled out", name),
+        )
+    };
+    progress(
+        RolloutKind::Deployment,
+        &deployment.metadata,
+        phase,
+        message,
+        counts,
+        deadline_exceeded,
+    )
+}
+
+// 与 kubectl 的 StatefulSetStatusViewer 判断一致
+fn statefulset_progress(statefulset: &StatefulSet) -> RolloutProgress {
+    let spec = statefulset.spec.clone().unwrap_or_default();
+    let status = statefulset.status.clone().unwrap_or_default();
+    let replicas = spec.replicas.unwrap_or(1);
+    let counts = StatusCounts {
+        replicas,
+        updated: status.updated_replicas.unwrap_or(0),
+        ready: status.ready_replicas.unwrap_or(0),
+        available: status.available_replicas.unwrap_or(0),
+    };
+    let strategy = spec.update_strategy.unwrap_or_default();
+    let rolling = strategy.type_.as_deref().unwrap_or("RollingUpdate") == "RollingUpdate";
+    let partition = strategy
+        .rolling_update
+        .and_then(|rolling_update| rolling_update.partition);
+    let update_revision = status.update_revision.unwrap_or_default();
+    let (phase, message) = if !rolling {
+        (
+            RolloutPhase::Failed,
+            "rollout status is only available for RollingUpdate strategy type".to_string(),
+        )
+    } else if !statefulset_observed(statefulset) {
+        (
+            RolloutPhase::Progressing,
+            "Waiting for statefulset spec update to be observed".to_string(),
+        )
+    } else if counts.ready < replicas {
+        (
+            RolloutPhase::Progressing,
+            format!("Waiting for {} pods to be ready", replicas - counts.ready),
+        )
+    } else if let Some(partition) = partition {
+        if counts.updated < replicas - partition {
+            (
+                RolloutPhase::Progressing,
+                format!(
+                    "Waiting for partitioned roll out to finish: {} out of {} new pods have been updated",
+                    counts.updated,
+                    replicas - partition
+                ),
+            )
+        } else {
+            (
+                RolloutPhase::Complete,
+                format!(
+                    "partitioned roll out complete: {} new pods have been updated",
+                    counts.updated
+                ),
+            )
+        }
+    } else if Some(&update_revision) != status.current_revision.as_ref() {
+        (
+            RolloutPhase::Progressing,
+            format!(
+                "waiting for statefulset rolling update to complete {} pods at revision {}",
+                counts.updated, update_revision
+            ),
+        )
+    } else {
+        (
+            RolloutPhase::Complete,
+            format!(
+                "statefulset rolling update complete {} pods at revision {}",
+                status.current_replicas.unwrap_or(0),
+                update_revision
+            ),
+        )
+    };
+    progress(
+        RolloutKind::StatefulSet,
+        &statefulset.metadata,
+        phase,
+        message,
+        counts,
+        false,
+    )
+}
+
+// 与 kubectl 的 DaemonSetStatusViewer 判断一致
+fn daemonset_progress(daemonset: &DaemonSet) -> RolloutProgress {
+    let name = daemonset.name_any();
+    let status = daemonset.status.clone().unwrap_or_default();
+    let counts = StatusCounts {
+        replicas: status.desired_number_scheduled,
+        updated: status.updated_number_scheduled.unwrap_or(0),
+        ready: status.number_ready,
+        available: status.number_available.unwrap_or(0),
+    };
+    let rolling = daemonset
+        .spec
+        .as_ref()
+        .and_then(|spec| spec.update_strategy.as_ref())
+        .and_then(|strategy| strategy.type_.as_deref())
+        .unwrap_or("RollingUpdate")
+        == "RollingUpdate";
+    let (phase, message) = if !rolling {
+        (
+            RolloutPhase::Failed,
+            "rollout status is only available for RollingUpdate strategy type".to_string(),
+        )
+    } else if !daemonset_observed(daemonset) {
+        (
+            RolloutPhase::Progressing,
+            "Waiting for daemon set spec update to be observed".to_string(),
+        )
+    } else if counts.updated < counts.replicas {
+        (
+            RolloutPhase::Progressing,
+            format!(
+                "Waiting for daemon set {:?} rollout to finish: {} out of {} new pods have been updated",
+                name, counts.updated, counts.replicas
+            ),
+        )
+    } else if counts.available < counts.replicas {
+        (
+            RolloutPhase::Progressing,
+            format!(
+                "Waiting for daemon set {:?} rollout to finish: {} of {} updated pods are available",
+                name, counts.available, counts.replicas
+            ),
+        )
+    } else {
+        (
+            RolloutPhase::Complete,
+            format!("daemon set {:?} successfully rolled out", name),
+        )
+    };
+    progress(
+        RolloutKind::DaemonSet,
+        &daemonset.metadata,
+        phase,
+        message,
+        counts,
+        false,
+    )
+}
+
+fn deleted_progress(kind: RolloutKind, namespace: &str, name: &str) -> RolloutProgress {
+    RolloutProgress {
+        kind: kind.as_str().to_string(),
+        namespace: namespace.to_string(),
+        name: name.to_string(),
+        phase: RolloutPhase::Failed,
+        message: format!("{} {:?} not found", kind.as_str().to_lowercase(), name),
+        replicas: 0,
+        updated_replicas: 0,
+        ready_replicas: 0,
+        available_replicas: 0,
+        deadline_exceeded: false,
+    }
+}
+
+fn watch_progress<K>(
+    api: Api<K>,
+    kind: RolloutKind,
+    namespace: &str,
+    name: &str,
+    evaluate: fn(&K) -> RolloutProgress,
+) -> BoxStream<'static, Result<RolloutProgress, MyError>>
+where
+    K: Resource + Clone + Debug + DeserializeOwned + Send + Sync + 'static,
+{
+    let namespace = namespace.to_string();
+    let name = name.to_string();
+    watch_object(api, &name.clone())
+        .default_backoff()
+        .map(move |object| match object? {
+            Some(object) => Ok(evaluate(&object)),
+            None => Ok(deleted_progress(kind, &namespace, &name)),
+        })
+        .boxed()
+}
+
+/// 监听工作负载，每次状态变化产生一次进度，直到 phase 不再是 Progressing
+pub fn rollout_status(
+    client: Client,
+    namespace: &str,
+    kind: RolloutKind,
+    name: &str,
+) -> BoxStream<'static, Result<RolloutProgress, MyError>> {
+    match kind {
+        RolloutKind::Deployment => watch_progress(
+            Api::<Deployment>::namespaced(client, namespace),
+            kind,
+            namespace,
+            name,
+            deployment_progress,
+        ),
+        RolloutKind::StatefulSet => watch_progress(
+            Api::<StatefulSet>::namespaced(client, namespace),
+            kind,
+            namespace,
+            name,
+            statefulset_progress,
+        ),
+        RolloutKind::DaemonSet => watch_progress(
+            Api::<DaemonSet>::namespaced(client, namespace),
+            kind,
+            namespace,
+            name,
+            daemonset_progress,
+        ),
+    }
+}
+
+const CHANGE_CAUSE_ANNOTATION: &str = "kubernetes.io/change-cause";
+
+// 历史中的一个修订，template 已去掉控制器添加的字段
+struct RevisionTemplate {
+    revision: i64,
+    meta: ObjectMeta,
+    template: serde_json::Value,
+    current: bool,
+}
+
+fn template_images(template: &serde_json::Value) -> Vec<String> {
+    ["initContainers", "containers"]
+        .iter()
+        .filter_map(|field| template["spec"][field].as_array())
+        .flatten()
+        .filter_map(|container| container["image"].as_str())
+        .map(|image| image.to_string())
+        .collect()
+}
+
+fn strip_template_hash(template: &mut serde_json::Value) {
+    if let Some(labels) = template["metadata"]["labels"].as_object_mut() {
+        labels.remove(POD_TEMPLATE_HASH);
+    }
+}
+
+fn template_diff(
+    previous: &RevisionTemplate,
+    current: &RevisionTemplate,
+) -> Result<String, MyError> {
+    let old = serde_yaml::to_string(&previous.template)?;
+    let new = serde_yaml::to_string(&current.template)?;
+    Ok(TextDiff::from_lines(&old, &new)
+        .unified_diff()
+        .header(
+            &format!("revision {}", previous.revision),
+            &format!("revision {}", current.revision),
+        )
+        .to_string())
+}
+
+// ControllerRevision 的 data 是 {"spec":{"template":{..., "$patch":"replace"}}} 形式的补丁
+fn controller_revision_template(revision: &ControllerRevision) -> serde_json::Value {
+    let mut template = revision
+        .data
+        .as_ref()
+        .map(|data| data.0["spec"]["template"].clone())
+        .unwrap_or_default();
+    if let Some(template) = template.as_object_mut() {
+        template.remove("$patch");
+    }
+    template
+}
+
+async fn revision_templates(
+    client: Client,
+    namespace: &str,
+    kind: RolloutKind,
+    name: &str,
+) -> Result<Vec<RevisionTemplate>, MyError> {
+    let revisions = match kind {
+        RolloutKind::Deployment => {
+            let deployment = Api::<Deployment>::namespaced(client.clone(), namespace)
+                .get(name)
+                .await?;
+            let current = revision_of(&deployment.metadata);
+            deployment_replica_sets(client, &deployment)
+                .await?
+                .into_iter()
+                .map(|(revision, replica_set)| {
+                    let mut template = serde_json::to_value(
+                        replica_set
+                            .spec
+                            .and_then(|spec| spec.template)
+                            .unwrap_or_default(),
+                    )
+                    .unwrap_or_default();
+                    strip_template_hash(&mut template);
+                    RevisionTemplate {
+                        revision,
+                        meta: replica_set.metadata,
+                        template,
+                        current: Some(revision) == current,
+                    }
+                })
+                .collect()
+        }
+        RolloutKind::StatefulSet => {
+            let statefulset = Api::<StatefulSet>::namespaced(client.clone(), namespace)
+                .get(name)
+                .await?;
+            let selector = statefulset
+                .spec
+                .as_ref()
+                .map(|spec| spec.selector.clone())
+                .unwrap_or_default();
+            let update_revision = statefulset
+                .status
+                .as_ref()
+                .and_then(|status| status.update_revision.clone());
+            controller_revisions(client, &statefulset.metadata, &selector)
+                .await?
+                .into_iter()
+                .map(|revision| RevisionTemplate {
+                    revision: revision.revision,
+                    template: controller_revision_template(&revision),
+                    current: revision.metadata.name == update_revision,
+                    meta: revision.metadata,
+                })
+                .collect()
+        }
+        RolloutKind::DaemonSet => {
+            let daemonset = Api::<DaemonSet>::namespaced(client.clone(), namespace)
+                .get(name)
+                .await?;
+            let selector = daemonset
+                .spec
+                .as_ref()
+                .map(|spec| spec.selector.clone())
+                .unwrap_or_default();
+            let revisions = controller_revisions(client, &daemonset.metadata, &selector).await?;
+            // DaemonSet 没有记录当前修订名，修订号最大的就是当前模板
+            let latest = revisions.last().map(|revision| revision.revision);
+            revisions
+                .into_iter()
+                .map(|revision| RevisionTemplate {
+                    revision: revision.revision,
+                    template: controller_revision_template(&revision),
+                    current: Some(revision.revision) == latest,
+                    meta: revision.metadata,
+                })
+                .collect()
+        }
+    };
+    Ok(revisions)
+}
+
+/// 列出工作负载的修订历史，按修订号从小到大排列，每个修订附带与上一个修订的模板差异
+pub async fn history(
+    client: Client,
+    namespace: &str,
+    kind: RolloutKind,
+    name: &str,
+) -> Result<Vec<RolloutRevision>, MyError> {
+    let templates = revision_templates(client, namespace, kind, name).await?;
+    let mut history = Vec::with_capacity(templates.len());
+    for (index, revision) in templates.iter().enumerate() {
+        let diff = match index.checked_sub(1).map(|previous| &templates[previous]) {
+            Some(previous) => Some(template_diff(previous, revision)?),
+            None => None,
+        };
+        history.push(RolloutRevision {
+            revision: revision.revision,
+            name: revision.meta.name.clone().unwrap_or_default(),
+            change_cause: revision
+                .meta
+                .annotations
+                .as_ref()
+                .and_then(|annotations| annotations.get(CHANGE_CAUSE_ANNOTATION))
+                .cloned(),
+            created: revision.meta.creation_timestamp.as_ref().map(|time| time.0),
+            images: template_images(&revision.template),
+            current: revision.current,
+            diff,
+        });
+    }
+    Ok(history)
+}
@@ -0,0 +1,247 @@
+use k8s_openapi::api::{
+    apps::v1::{Deployment, StatefulSet},
+    autoscaling::v2::HorizontalPodAutoscaler,
+};
+use kube::{
+    api::{Api, DynamicObject, ListParams, Patch, PatchParams},
+    core::ApiResource,
+    Client, Discovery, ResourceExt,
+};
+
+use crate::{error::MyError, resource::scale::ScaleResult, utils::discovery::matching_kinds};
+
+// 缩容到 0 前记录的副本数
+pub const PREVIOUS_REPLICAS_ANNOTATION: &str = "ksm/previous-replicas";
+
+/// 在所有匹配 kind 或复数名的资源类型中查找带 scale 子资源的
+pub fn scalable_resource(
+    discovery: &Discovery,
+    group: Option<&str>,
+    kind: &str,
+) -> Result<ApiResource, MyError> {
+    let mut found = false;
+    for (resource, capabilities) in matching_kinds(discovery, group, kind) {
+        found = true;
+        if capabilities
+            .subresources
+            .iter()
+            .any(|(subresource, _)| subresource.plural == "scale")
+        {
+            return Ok(resource);
+        }
+    }
+    Err(MyError::UnsupportedKind(if found {
+        format!("{} has no scale subresource", kind)
+    } else {
+        kind.to_string()
+    }))
+}
+
+fn api_group(api_version: &str) -> &str {
+    api_version.rsplit_once('/').map_or("", |(group, _)| group)
+}
+
+// 指向该工作负载的 HPA 会在下一次同步时覆盖手动设置的副本数
+// 只用于提示，无权限列出 HPA 或集群没有 autoscaling/v2 时不返回警告
+async fn hpa_warnings(
+    client: Client,
+    namespace: &str,
+    resource: &ApiResource,
+    name: &str,
+    replicas: i32,
+) -> Vec<String> {
+    let hpas = match Api::<HorizontalPodAutoscaler>::namespaced(client, namespace)
+        .list(&ListParams::default())
+        .await
+    {
+        Ok(hpas) => hpas,
+        Err(e) => {
+            tracing::debug!("Failed to list HPAs in {}: {}", namespace, e);
+            return Vec::new();
+        }
+    };
+    let warnings = hpas
+        .items
+        .iter()
+        .filter_map(|hpa| {
+            let spec = hpa.spec.as_ref()?;
+            let target = &spec.scale_target_ref;
+            let same_group = api_group(target.api_version.as_deref().unwrap_or_default())
+                == resource.group;
+            if target.kind != resource.kind || target.name != name || !same_group {
+                return None;
+            }
+            let min = spec.min_replicas.unwrap_or(1);
+            let max = spec.max_replicas;
+            Some(if replicas == 0 {
+                format!(
+                    "HorizontalPodAutoscaler {} targets this workload and stops scaling while replicas is 0",
+                    hpa.name_any()
+                )
+            } else if replicas < min || replicas > max {
+                format!(
+                    "HorizontalPodAutoscaler {} keeps replicas between {} and {}, {} will be overridden",
+                    hpa.name_any(),
+                    min,
+                    max,
+                    replicas
+                )
+            } else {
+                format!(
+                    "HorizontalPodAutoscaler {} targets this workload and may override the replica count",
+                    hpa.name_any()
+                )
+            })
+        })
+        .collect();
+    warnings
+}
+
+/// 通过 scale 子资源修改副本数
+pub async fn scale(
+    client: Client,
+    namespace: &str,
+    resource: &ApiResource,
+    name: &str,
+    replicas: i32,
+) -> Result<ScaleResult, MyError> {
+    let api = Api::<DynamicObject>::namespaced_with(client.clone(), namespace, resource);
+    let previous = api
+        .get_scale(name)
+        .await?
+        .spec
+        .and_then(|spec| spec.replicas)
+        .unwrap_or(0);
+    let patch = serde_json::json!({ "spec": { "replicas": replicas } });
+    api.patch_scale(name, &PatchParams::default(), &Patch::Merge(&patch))
+        .await?;
+    let warnings = hpa_warnings(client, namespace, resource, name, replicas).await;
+    Ok(ScaleResult {
+        kind: resource.kind.clone(),
+        namespace: namespace.to_string(),
+        name: name.to_string(),
+        previous,
+        replicas,
+        warnings,
+    })
+}
+
+/// 命名空间级别缩放涉及的工作负载类型
+pub fn namespace_workloads() -> [ApiResource; 2] {
+    [
+        ApiResource::erase::<Deployment>(&()),
+        ApiResource::erase::<StatefulSet>(&()),
+    ]
+}
+
+async fn annotate_previous(
+    api: &Api<DynamicObject>,
+    name: &str,
+    previous: Option<i32>,
+) -> Result<(), MyError> {
+    let patch = serde_json::json!({
+        "metadata": {
+            "annotations": {
+                PREVIOUS_REPLICAS_ANNOTATION: previous.map(|replicas| replicas.to_string()),
+            }
+        }
+    });
+    api.patch(name, &PatchParams::default(), &Patch::Merge(&patch))
+        .await?;
+    Ok(())
+}
+
+/// 命名空间内副本数大于 0 的 Deployment 和 StatefulSet 及其副本数
+pub async fn running_workloads(
+    client: Client,
+    namespace: &str,
+) -> Result<Vec<(ApiResource, String, i32)>, MyError> {
+    let mut workloads = Vec::new();
+    for resource in namespace_workloads() {
+        let api = Api::<DynamicObject>::namespaced_with(client.clone(), namespace, &resource);
+        for object in api.list(&ListParams::default()).await?.items {
+            let name = object.name_any();
+            let replicas = api
+                .get_scale(&name)
+                .await?
+                .spec
+                .and_then(|spec| spec.replicas)
+                .unwrap_or(0);
+            if replicas > 0 {
+                workloads.push((resource.clone(), name, replicas));
+            }
+        }
+    }
+    Ok(workloads)
+}
+
+/// 把原副本数记录到注解后缩容到 0
+pub async fn scale_to_zero(
+    client: Client,
+    namespace: &str,
+    resource: &ApiResource,
+    name: &str,
+    replicas: i32,
+) -> Result<ScaleResult, MyError> {
+    let api = Api::<DynamicObject>::namespaced_with(client.clone(), namespace, resource);
+    // 先记录再缩容，缩容失败时注解也能用于恢复
+    annotate_previous(&api, name, Some(replicas)).await?;
+    scale(client, namespace, resource, name, 0).await
+}
+
+/// 按注解中记录的副本数恢复工作负载并删除注解，对象不存在或没有注解时返回空
+pub async fn restore(
+    client: Client,
+    namespace: &str,
+    resource: &ApiResource,
+    name: &str,
+) -> Result<Option<ScaleResult>, MyError> {
+    let api = Api::<DynamicObject>::namespaced_with(client.clone(), namespace, resource);
+    let Some(object) = api.get_opt(name).await? else {
+        return Ok(None);
+    };
+    let Some(replicas) = object
+        .annotations()
+        .get(PREVIOUS_REPLICAS_ANNOTATION)
+        .and_then(|replicas| replicas.parse::<i32>().ok())
+    else {
+        return Ok(None);
+    };
+    let result = scale(client, namespace, resource, name, replicas).await?;
+    annotate_previous(&api, name, None).await?;
+    Ok(Some(result))
+}
+
+/// 把命名空间内的 Deployment 和 StatefulSet 缩容到 0，原副本数记录在注解中
+pub async fn scale_namespace_to_zero(
+    client: Client,
+    namespace: &str,
+) -> Result<Vec<ScaleResult>, MyError> {
+    let mut results = Vec::new();
+    for (resource, name, replicas) in running_workloads(client.clone(), namespace).await? {
+        results.push(scale_to_zero(client.clone(), namespace, &resource, &name, replicas).await?);
+    }
+    Ok(results)
+}
+
+/// 按注解中记录的副本数恢复命名空间内的工作负载，并删除注解
+pub async fn restore_namespace(
+    client: Client,
+    namespace: &str,
+) -> Result<Vec<ScaleResult>, MyError> {
+    let mut results = Vec::new();
+    for resource in namespace_workloads() {
+        let api = Api::<DynamicObject>::namespaced_with(client.clone(), namespace, &resource);
+        for object in api.list(&ListParams::default()).await?.items {
+            if !object
+                .annotations()
+                .contains_key(PREVIOUS_REPLICAS_ANNOTATION)
+            {
+                continue;
+            }
+            let name = object.name_any();
+            results.extend(restore(client.clone(), namespace, &resource, &name).await?);
+        }
+    }
+    Ok(results)
+}
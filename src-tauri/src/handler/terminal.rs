@@ -0,0 +1,281 @@
+use std::time::Duration;
+
+use futures::SinkExt;
+use http::StatusCode;
+use k8s_openapi::api::core::v1::Pod;
+use kube::{
+    api::{Api, AttachParams, AttachedProcess, TerminalSize},
+    client::UpgradeConnectionError,
+};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+    select,
+};
+use uuid::Uuid;
+
+use crate::{
+    boot::websocket::{ChannelInput, ChannelKind, StdStream, WebsocketManager},
+    error::MyError,
+    handler::recording::Recorder,
+    utils::utf8::Utf8Decoder,
+};
+
+// 未指定命令时依次尝试的 shell
+pub const DEFAULT_SHELLS: &[&[&str]] = &[&["bash"], &["sh"], &["ash"], &["busybox", "sh"]];
+
+// 探测 shell 时等待进程退出的时间
+const SHELL_PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone)]
+pub struct TerminalOptions {
+    // 以二进制帧原样转发输出，否则按 UTF-8 解码后以文本帧发送
+    pub binary: bool,
+    pub cols: Option<u16>,
+    pub rows: Option<u16>,
+    // 录制会话的输入输出和终端大小变化
+    pub recorder: Option<Recorder>,
+}
+
+// exec/attach 被 RBAC 拒绝时 apiserver 返回 403，升级 websocket 失败
+pub fn exec_error(e: kube::Error) -> MyError {
+    match &e {
+        kube::Error::Api(resp) if resp.code == StatusCode::FORBIDDEN.as_u16() => {
+            MyError::Forbidden(resp.message.clone())
+        }
+        kube::Error::UpgradeConnection(UpgradeConnectionError::ProtocolSwitch(code))
+            if *code == StatusCode::FORBIDDEN =>
+        {
+            MyError::Forbidden(e.to_string())
+        }
+        _ => MyError::KubeError(format!("Failed to exec into pod: {}", e)),
+    }
+}
+
+// 依次用 `<shell> -c 'exit 0'` 探测候选 shell，返回第一个能正常退出的
+pub async fn detect_shell(
+    pods: &Api<Pod>,
+    name: &str,
+    container: &str,
+    candidates: &[Vec<String>],
+) -> Result<Vec<String>, MyError> {
+    let mut failures = Vec::new();
+    for shell in candidates {
+        let mut command = shell.clone();
+        command.extend(["-c".to_string(), "exit 0".to_string()]);
+        let params = AttachParams::default()
+            .container(container)
+            .stdin(false)
+            .stdout(true)
+            .stderr(true);
+        let mut attached = pods
+            .exec(name, command, &params)
+            .await
+            .map_err(exec_error)?;
+
+        let status = match attached.take_status() {
+            Some(status) => tokio::time::timeout(SHELL_PROBE_TIMEOUT, status)
+                .await
+                .ok()
+                .flatten(),
+            None => None,
+        };
+        if status.as_ref().and_then(|s| s.status.as_deref()) == Some("Success") {
+            return Ok(shell.clone());
+        }
+        let message = status.and_then(|s| s.message).unwrap_or_default();
+        tracing::info!(
+            "Shell {:?} not usable in {}/{}: {}",
+            shell,
+            name,
+            container,
+            message
+        );
+        failures.push(format!("{}: {}", shell.join(" "), message));
+    }
+    Err(MyError::NoShell(format!(
+        "no usable shell in container {} ({})",
+        container,
+        failures.join("; ")
+    )))
+}
+
+async fn pipe_output<R: AsyncRead + Unpin>(
+    mut reader: R,
+    ws_manager: WebsocketManager,
+    client_id: Uuid,
+    channel_id: String,
+    stream: StdStream,
+    binary: bool,
+    recorder: Option<Recorder>,
+) {
+    let mut buffer = [0u8; 4096];
+    let mut decoder = Utf8Decoder::default();
+    loop {
+        let read = reader.read(&mut buffer).await;
+        if let (Ok(n), Some(recorder)) = (&read, &recorder) {
+            recorder.output(stream, &buffer[..*n]);
+        }
+        let result = match read {
+            Ok(0) => break, // EOF
+            Ok(n) if binary => {
+                ws_manager
+                    .send_bytes(client_id, &channel_id, stream, buffer[..n].to_vec())
+                    .await
+            }
+            Ok(n) => {
+                let text = decoder.decode(&buffer[..n]);
+                if text.is_empty() {
+                    continue;
+                }
+                ws_manager
+                    .send_text(client_id, &channel_id, stream, text)
+                    .await
+            }
+            Err(e) => {
+                eprintln!("Error reading {:?}: {}", stream, e);
+                break;
+            }
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to send {:?}: {}", stream, e);
+            return;
+        }
+    }
+
+    let rest = decoder.finish();
+    if !rest.is_empty() {
+        let _ = ws_manager
+            .send_text(client_id, &channel_id, stream, rest)
+            .await;
+    }
+}
+
+// 将 exec/attach 得到的进程接到 websocket channel 上，直到进程退出或客户端关闭 channel
+pub async fn run_session(
+    ws_manager: WebsocketManager,
+    client_id: Uuid,
+    channel_id: String,
+    mut attached: AttachedProcess,
+    options: TerminalOptions,
+) -> Result<(), MyError> {
+    let stdout = attached
+        .stdout()
+        .ok_or_else(|| MyError::KubeError("No stdout available".to_string()))?;
+    // tty 模式下 stderr 合并在 stdout 中
+    let stderr = attached.stderr();
+    // attach 到没有开启 stdin 的容器时只转发输出
+    let stdin = attached.stdin();
+    let mut terminal_size = attached.terminal_size();
+    if let (Some(sender), Some(cols), Some(rows)) =
+        (terminal_size.as_mut(), options.cols, options.rows)
+    {
+        sender
+            .send(TerminalSize {
+                width: cols,
+                height: rows,
+            })
+            .await
+            .map_err(|e| MyError::KubeError(format!("Failed to set terminal size: {}", e)))?;
+    }
+
+    let (input_tx, input_rx) = match stdin {
+        Some(_) => {
+            let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<ChannelInput>();
+            (Some(tx), Some(rx))
+        }
+        None => (None, None),
+    };
+    let cancel = ws_manager
+        .open_channel(client_id, &channel_id, ChannelKind::Exec, input_tx)
+        .await
+        .map_err(MyError::WebsocketError)?;
+
+    let stdout_task = tokio::spawn(pipe_output(
+        stdout,
+        ws_manager.clone(),
+        client_id,
+        channel_id.clone(),
+        StdStream::Stdout,
+        options.binary,
+        options.recorder.clone(),
+    ));
+    let stderr_task = stderr.map(|stderr| {
+        tokio::spawn(pipe_output(
+            stderr,
+            ws_manager.clone(),
+            client_id,
+            channel_id.clone(),
+            StdStream::Stderr,
+            options.binary,
+            options.recorder.clone(),
+        ))
+    });
+
+    let recorder = options.recorder.clone();
+    let mut stdin_task = tokio::spawn(async move {
+        let (Some(mut stdin), Some(mut input_rx)) = (stdin, input_rx) else {
+            // 没有输入时等待输出结束
+            return std::future::pending().await;
+        };
+        while let Some(input) = input_rx.recv().await {
+            match input {
+                ChannelInput::Data(data) => {
+                    if let Some(recorder) = &recorder {
+                        recorder.input(&data);
+                    }
+                    if let Err(e) = stdin.write_all(&data).await {
+                        eprintln!("Failed to write to stdin: {}", e);
+                        break;
+                    }
+                    if let Err(e) = stdin.flush().await {
+                        eprintln!("Failed to flush stdin: {}", e);
+                        break;
+                    }
+                }
+                ChannelInput::Resize { cols, rows } => {
+                    if let Some(recorder) = &recorder {
+                        recorder.resize(cols, rows);
+                    }
+                    // 非 tty 会话没有终端大小，忽略
+                    let Some(sender) = terminal_size.as_mut() else {
+                        continue;
+                    };
+                    let size = TerminalSize {
+                        width: cols,
+                        height: rows,
+                    };
+                    if let Err(e) = sender.send(size).await {
+                        eprintln!("Failed to resize terminal: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    });
+
+    select! {
+        _ = stdout_task => {
+            println!("Stdout task completed for client {} channel {}", client_id, channel_id);
+            // 进程退出后把剩余的 stderr 发完
+            if let Some(stderr_task) = stderr_task {
+                select! {
+                    _ = stderr_task => {}
+                    _ = cancel.cancelled() => {}
+                }
+            }
+        }
+        _ = &mut stdin_task => {
+            println!("Stdin task completed for client {} channel {}", client_id, channel_id);
+        }
+        _ = cancel.cancelled() => {
+            println!("Channel {} closed by client {}", channel_id, client_id);
+        }
+    }
+    stdin_task.abort();
+    if let Some(recorder) = &options.recorder {
+        recorder.finish();
+    }
+
+    ws_manager.close_channel(client_id, &channel_id).await;
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use k8s_openapi::{
+    api::{
+        apps::v1::{DaemonSet, Deployment, ReplicaSet, StatefulSet},
+        batch::v1::Job,
+        core::v1::Pod,
+    },
+    apimachinery::pkg::apis::meta::v1::LabelSelector,
+};
+use kube::{
+    api::{Api, ListParams},
+    core::Selector,
+    Client,
+};
+
+use crate::error::MyError;
+
+/// 读取工作负载的 spec.selector
+pub async fn workload_selector(
+    client: Client,
+    namespace: &str,
+    kind: &str,
+    name: &str,
+) -> Result<Selector, MyError> {
+    let label_selector: Option<LabelSelector> = match kind.to_lowercase().as_str() {
+        "deployment" => Api::<Deployment>::namespaced(client, namespace)
+            .get(name)
+            .await?
+            .spec
+            .map(|spec| spec.selector),
+        "statefulset" => Api::<StatefulSet>::namespaced(client, namespace)
+            .get(name)
+            .await?
+            .spec
+            .map(|spec| spec.selector),
+        "daemonset" => Api::<DaemonSet>::namespaced(client, namespace)
+            .get(name)
+            .await?
+            .spec
+            .map(|spec| spec.selector),
+        "replicaset" => Api::<ReplicaSet>::namespaced(client, namespace)
+            .get(name)
+            .await?
+            .spec
+            .map(|spec| spec.selector),
+        "job" => Api::<Job>::namespaced(client, namespace)
+            .get(name)
+            .await?
+            .spec
+            .and_then(|spec| spec.selector),
+        _ => return Err(MyError::UnsupportedKind(kind.to_string())),
+    };
+
+    let label_selector = label_selector
+        .ok_or_else(|| MyError::OtherError(format!("{} {} has no selector", kind, name)))?;
+    Selector::try_from(label_selector).map_err(|e| MyError::OtherError(e.to_string()))
+}
+
+/// 列出工作负载当前管理的所有 Pod
+pub async fn workload_pods(
+    client: Client,
+    namespace: &str,
+    kind: &str,
+    name: &str,
+) -> Result<Vec<Pod>, MyError> {
+    let selector = workload_selector(client.clone(), namespace, kind, name).await?;
+    let pods: Api<Pod> = Api::namespaced(client, namespace);
+    let list = pods
+        .list(&ListParams::default().labels_from(&selector))
+        .await?;
+    Ok(list.items)
+}
@@ -0,0 +1,18 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileCopyProgress {
+    pub id: String,
+    pub bytes: u64,
+    // 上传时为本地文件总大小，下载时未知
+    pub total: Option<u64>,
+    pub finished: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileCopyResult {
+    pub id: String,
+    pub path: String,
+    pub bytes: u64,
+}
@@ -0,0 +1,23 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DependentNode {
+    pub api_version: String,
+    pub kind: String,
+    pub namespace: Option<String>,
+    pub name: String,
+    pub uid: String,
+    // 前台删除时会阻塞 owner 的删除
+    pub block_owner_deletion: bool,
+    pub children: Vec<DependentNode>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeleteResult {
+    pub kind: String,
+    pub namespace: Option<String>,
+    pub name: String,
+    pub uid: Option<String>,
+    // 对象已从 API 中消失；等待超时或未等待时为 false
+    pub deleted: bool,
+}
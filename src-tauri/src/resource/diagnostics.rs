@@ -0,0 +1,31 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    // 产生该结论的规则名
+    pub rule: String,
+    pub severity: Severity,
+    pub container: Option<String>,
+    pub title: String,
+    pub detail: String,
+    pub suggestions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PodDiagnostics {
+    pub namespace: String,
+    pub name: String,
+    pub phase: Option<String>,
+    pub node: Option<String>,
+    pub ready: bool,
+    // 按严重程度从高到低排列
+    pub findings: Vec<Finding>,
+}
@@ -0,0 +1,35 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileKind {
+    File,
+    Directory,
+    Symlink,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileEntry {
+    pub name: String,
+    pub path: String,
+    pub kind: FileKind,
+    pub size: u64,
+    // 权限位，例如 0o755
+    pub mode: u32,
+    // ls -l 风格，例如 drwxr-xr-x
+    pub permissions: String,
+    // 只有 ls 的镜像无法得到精确的修改时间
+    pub mtime: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileContent {
+    pub path: String,
+    // 非 UTF-8 内容为空并标记 binary
+    pub content: Option<String>,
+    pub binary: bool,
+    pub bytes: usize,
+    // 超过读取上限被截断
+    pub truncated: bool,
+}
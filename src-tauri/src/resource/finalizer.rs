@@ -0,0 +1,35 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusCondition {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub status: String,
+    pub reason: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RemainingResource {
+    pub api_version: String,
+    pub kind: String,
+    pub count: usize,
+    // 最多列出 20 个名称
+    pub names: Vec<String>,
+    pub finalizers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FinalizerDiagnostics {
+    pub kind: String,
+    pub namespace: Option<String>,
+    pub name: String,
+    pub terminating: bool,
+    pub deletion_timestamp: Option<DateTime<Utc>>,
+    pub finalizers: Vec<String>,
+    // 以下只对命名空间有效
+    pub spec_finalizers: Vec<String>,
+    pub conditions: Vec<StatusCondition>,
+    pub remaining: Vec<RemainingResource>,
+}
@@ -0,0 +1,42 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphNode {
+    // kind/namespace/name
+    pub id: String,
+    pub kind: String,
+    pub namespace: Option<String>,
+    pub name: String,
+    pub uid: Option<String>,
+    pub root: bool,
+    // 被引用但不存在（或无权限读取）的对象
+    pub missing: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphRelation {
+    // ownerReferences
+    Owns,
+    // Service 的 selector 选中 Pod
+    Selects,
+    // Ingress 转发到 Service
+    Routes,
+    // Pod 挂载或引用 ConfigMap/Secret/PVC
+    Mounts,
+    // Pod 使用的 ServiceAccount
+    Uses,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub relation: GraphRelation,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
@@ -0,0 +1,21 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogExportProgress {
+    pub path: String,
+    pub pod: String,
+    pub container: String,
+    pub index: usize,
+    pub total: usize,
+    pub bytes: u64,
+    pub finished: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogExportResult {
+    pub path: String,
+    pub bytes: u64,
+    pub sources: usize,
+    pub failed: Vec<String>,
+}
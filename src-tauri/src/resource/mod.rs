@@ -1 +1,14 @@
 pub mod cluster;
+pub mod copy;
+pub mod delete;
+pub mod diagnostics;
+pub mod file;
+pub mod finalizer;
+pub mod graph;
+pub mod log;
+pub mod namespace_sleep;
+pub mod port_forward;
+pub mod recording;
+pub mod rollout;
+pub mod scale;
+pub mod websocket;
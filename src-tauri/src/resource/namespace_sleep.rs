@@ -0,0 +1,23 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::resource::scale::ScaleResult;
+
+/// 休眠前的状态，以 JSON 形式保存在命名空间注解中
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NamespaceSleepState {
+    // kind -> 休眠时缩容的工作负载名称，副本数和缩容到 0 一样记录在各自的 ksm/previous-replicas 注解中
+    pub workloads: BTreeMap<String, Vec<String>>,
+    // CronJob 名称 -> 原来的 suspend
+    pub cronjobs: BTreeMap<String, bool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NamespaceSleepResult {
+    pub namespace: String,
+    pub workloads: Vec<ScaleResult>,
+    pub cronjobs: Vec<String>,
+    // 唤醒时已不存在的资源
+    pub skipped: Vec<String>,
+}
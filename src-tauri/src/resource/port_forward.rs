@@ -0,0 +1,39 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PortForwardState {
+    Active,
+    Reconnecting,
+    Error,
+    Stopped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PortForwardInfo {
+    pub id: String,
+    // 由端口转发配置启动时为配置 id
+    pub profile: Option<String>,
+    pub namespace: String,
+    pub kind: String,
+    pub name: String,
+    pub address: String,
+    pub local_port: u16,
+    pub remote_port: u16,
+    // 当前转发到的 pod 及其容器端口
+    pub pod: String,
+    pub pod_port: u16,
+    pub state: PortForwardState,
+    pub error: Option<String>,
+    pub connections: usize,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PortForwardStats {
+    pub id: String,
+    pub connections: usize,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+}
@@ -0,0 +1,12 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingInfo {
+    pub id: String,
+    pub path: String,
+    pub title: Option<String>,
+    pub timestamp: Option<u64>,
+    pub width: u16,
+    pub height: u16,
+    pub size: u64,
+}
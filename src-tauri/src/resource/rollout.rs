@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RolloutResult {
+    pub kind: String,
+    pub namespace: String,
+    pub name: String,
+    // 操作生效后的修订号，控制器未及时处理时为空
+    pub revision: Option<i64>,
+    pub paused: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RolloutPhase {
+    Progressing,
+    Complete,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RolloutProgress {
+    pub kind: String,
+    pub namespace: String,
+    pub name: String,
+    pub phase: RolloutPhase,
+    // 与 kubectl rollout status 输出一致的描述
+    pub message: String,
+    pub replicas: i32,
+    pub updated_replicas: i32,
+    pub ready_replicas: i32,
+    pub available_replicas: i32,
+    pub deadline_exceeded: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RolloutRevision {
+    pub revision: i64,
+    // ReplicaSet 或 ControllerRevision 的名称
+    pub name: String,
+    pub change_cause: Option<String>,
+    pub created: Option<DateTime<Utc>>,
+    pub images: Vec<String>,
+    pub current: bool,
+    // 与上一个修订 pod 模板的 unified diff，第一个修订为空
+    pub diff: Option<String>,
+}
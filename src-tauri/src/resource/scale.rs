@@ -0,0 +1,12 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScaleResult {
+    pub kind: String,
+    pub namespace: String,
+    pub name: String,
+    pub previous: i32,
+    pub replicas: i32,
+    // 例如有 HPA 管理该工作负载，副本数可能被覆盖
+    pub warnings: Vec<String>,
+}
@@ -0,0 +1,25 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WebsocketInfo {
+    pub port: u16,
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClientStats {
+    pub client_id: String,
+    pub queued: usize,
+    pub sent: u64,
+    pub frames: u64,
+    pub dropped: u64,
+    pub coalesced: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChannelInfo {
+    pub client_id: String,
+    pub channel_id: String,
+    pub kind: crate::boot::websocket::ChannelKind,
+    pub interactive: bool,
+}
@@ -1,10 +1,51 @@
+use std::sync::Mutex;
+
 use tauri::{
-    menu::{Menu, MenuItem, Submenu},
+    menu::{IsMenuItem, Menu, MenuItem, Submenu},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    Emitter, Manager,
+    Emitter, Listener, Manager, Wry,
 };
 
-pub fn create_tray(app: &tauri::AppHandle) -> tauri::Result<()> {
+use crate::boot::setup::AppData;
+
+// 托盘中停止端口转发的菜单项 id 前缀，后接转发 id
+const PORT_FORWARD_STOP: &str = "port_forward_stop:";
+
+// 列出正在运行的端口转发，点击即停止
+fn port_forward_menu(app: &tauri::AppHandle) -> tauri::Result<Submenu<Wry>> {
+    let forwards = {
+        let state = app.state::<Mutex<AppData>>();
+        let app_data = state.lock().unwrap();
+        app_data.port_forward.list()
+    };
+    let mut items = Vec::new();
+    for forward in &forwards {
+        let text = format!(
+            "停止 {}:{} → {}/{}:{}",
+            forward.address,
+            forward.local_port,
+            forward.namespace,
+            forward.name,
+            forward.remote_port
+        );
+        let id = format!("{}{}", PORT_FORWARD_STOP, forward.id);
+        items.push(MenuItem::with_id(app, id, text, true, None::<&str>)?);
+    }
+    if items.is_empty() {
+        items.push(MenuItem::with_id(
+            app,
+            "port_forward_none",
+            "无",
+            false,
+            None::<&str>,
+        )?);
+    }
+    let items: Vec<&dyn IsMenuItem<Wry>> =
+        items.iter().map(|i| i as &dyn IsMenuItem<Wry>).collect();
+    Submenu::with_id_and_items(app, "port_forward", "端口转发", true, &items)
+}
+
+fn build_menu(app: &tauri::AppHandle) -> tauri::Result<Menu<Wry>> {
     let quit_i = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
     let show_i = MenuItem::with_id(app, "show", "显示", true, None::<&str>)?;
     let hide_i = MenuItem::with_id(app, "hide", "隐藏", true, None::<&str>)?;
@@ -14,8 +55,16 @@ pub fn create_tray(app: &tauri::AppHandle) -> tauri::Result<()> {
     let new_i = MenuItem::with_id(app, "new_file", "添加", true, None::<&str>)?;
 
     let a = Submenu::with_id_and_items(app, "File", "文件", true, &[&edit_i, &new_i])?;
+    let port_forward = port_forward_menu(app)?;
 
-    let menu = Menu::with_items(app, &[&quit_i, &show_i, &hide_i, &about_i, &a])?;
+    Menu::with_items(
+        app,
+        &[&quit_i, &show_i, &hide_i, &about_i, &a, &port_forward],
+    )
+}
+
+pub fn create_tray(app: &tauri::AppHandle) -> tauri::Result<()> {
+    let menu = build_menu(app)?;
     let _ = TrayIconBuilder::with_id("tray")
         .tooltip("something")
         .icon(app.default_window_icon().unwrap().clone())
@@ -40,6 +89,16 @@ pub fn create_tray(app: &tauri::AppHandle) -> tauri::Result<()> {
                 println!("new_file");
             }
             "about" => app.emit("tary_about", ()).unwrap(),
+            id if id.starts_with(PORT_FORWARD_STOP) => {
+                let manager = {
+                    let state = app.state::<Mutex<AppData>>();
+                    let app_data = state.lock().unwrap();
+                    app_data.port_forward.clone()
+                };
+                if let Err(e) = manager.stop(app, &id[PORT_FORWARD_STOP.len()..]) {
+                    tracing::warn!("Failed to stop port forward: {}", e);
+                }
+            }
             // Add more events here
             _ => {}
         })
@@ -104,5 +163,19 @@ pub fn create_tray(app: &tauri::AppHandle) -> tauri::Result<()> {
             _ => {}
         })
         .build(app);
+
+    // 端口转发启停时重建托盘菜单
+    let handle = app.clone();
+    app.listen("port_forward_state", move |_| {
+        let Some(tray) = handle.tray_by_id("tray") else {
+            return;
+        };
+        match build_menu(&handle) {
+            Ok(menu) => {
+                let _ = tray.set_menu(Some(menu));
+            }
+            Err(e) => tracing::warn!("Failed to rebuild tray menu: {}", e),
+        }
+    });
     Ok(())
 }
@@ -0,0 +1,77 @@
+use kube::{
+    api::{Api, DynamicObject},
+    discovery::{verbs, ApiCapabilities, ApiResource, Scope},
+    Client, Discovery,
+};
+
+use serde::Deserialize;
+
+use crate::error::MyError;
+
+/// 前端传入的任意对象引用
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResourceRef {
+    // 集群级资源忽略
+    pub namespace: Option<String>,
+    // 同名 kind 存在于多个 API 组时用于区分
+    pub group: Option<String>,
+    pub kind: String,
+    pub name: String,
+}
+
+impl ResourceRef {
+    pub fn resolve(
+        &self,
+        discovery: &Discovery,
+    ) -> Result<(ApiResource, ApiCapabilities), MyError> {
+        resolve_kind(discovery, self.group.as_deref(), &self.kind)
+    }
+}
+
+/// 按 kind 或复数名查找资源类型，group 为空时取第一个匹配的 API 组
+pub fn resolve_kind(
+    discovery: &Discovery,
+    group: Option<&str>,
+    kind: &str,
+) -> Result<(ApiResource, ApiCapabilities), MyError> {
+    matching_kinds(discovery, group, kind)
+        .next()
+        .ok_or_else(|| MyError::UnsupportedKind(kind.to_string()))
+}
+
+/// 所有 API 组中与 kind 或复数名匹配的资源类型，group 为空时不限制 API 组
+pub fn matching_kinds<'a>(
+    discovery: &'a Discovery,
+    group: Option<&'a str>,
+    kind: &'a str,
+) -> impl Iterator<Item = (ApiResource, ApiCapabilities)> + 'a {
+    discovery
+        .groups()
+        .filter(move |api_group| group.is_none_or(|group| group == api_group.name()))
+        .flat_map(|api_group| api_group.recommended_resources())
+        .filter(move |(resource, _)| {
+            resource.kind.eq_ignore_ascii_case(kind) || resource.plural.eq_ignore_ascii_case(kind)
+        })
+}
+
+/// 所有支持 list 的资源类型
+pub fn listable_resources(discovery: &Discovery) -> Vec<(ApiResource, ApiCapabilities)> {
+    discovery
+        .groups()
+        .flat_map(|api_group| api_group.recommended_resources())
+        .filter(|(_, capabilities)| capabilities.supports_operation(verbs::LIST))
+        .collect()
+}
+
+/// 集群级资源忽略 namespace，命名空间资源在 namespace 为空时跨所有命名空间
+pub fn dynamic_api(
+    client: Client,
+    resource: &ApiResource,
+    capabilities: &ApiCapabilities,
+    namespace: Option<&str>,
+) -> Api<DynamicObject> {
+    match (&capabilities.scope, namespace) {
+        (Scope::Namespaced, Some(namespace)) => Api::namespaced_with(client, namespace, resource),
+        _ => Api::all_with(client, resource),
+    }
+}
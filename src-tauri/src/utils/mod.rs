@@ -1 +1,3 @@
 pub mod cluster;
+pub mod discovery;
+pub mod utf8;
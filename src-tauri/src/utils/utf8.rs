@@ -0,0 +1,47 @@
+// 增量 UTF-8 解码，保留被读缓冲区截断的多字节字符，等下一块数据到达后再解码
+#[derive(Debug, Default)]
+pub struct Utf8Decoder {
+    pending: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    pub fn decode(&mut self, chunk: &[u8]) -> String {
+        self.pending.extend_from_slice(chunk);
+        let mut output = String::with_capacity(self.pending.len());
+        let mut rest: &[u8] = &self.pending;
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(text) => {
+                    output.push_str(text);
+                    rest = &[];
+                    break;
+                }
+                Err(e) => {
+                    let (valid, invalid) = rest.split_at(e.valid_up_to());
+                    // valid_up_to 之前的字节一定是合法 UTF-8
+                    output.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                    match e.error_len() {
+                        // 非法字节替换为 U+FFFD 后继续
+                        Some(len) => {
+                            output.push(char::REPLACEMENT_CHARACTER);
+                            rest = &invalid[len..];
+                        }
+                        // 末尾是不完整的字符，留到下一次
+                        None => {
+                            rest = invalid;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        self.pending = rest.to_vec();
+        output
+    }
+
+    // 流结束时剩余的不完整字节按替换字符输出
+    pub fn finish(&mut self) -> String {
+        let rest = std::mem::take(&mut self.pending);
+        String::from_utf8_lossy(&rest).into_owned()
+    }
+}
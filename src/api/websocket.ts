@@ -0,0 +1,104 @@
+import { invoke } from "@tauri-apps/api/core";
+import WebSocket, { Message } from "@tauri-apps/plugin-websocket";
+
+interface WebsocketInfo {
+  port: number;
+  token: string;
+}
+
+// 本地 websocket 服务每次启动使用随机端口和 token
+export const connectWebsocket = async () => {
+  const info = await invoke<WebsocketInfo>("websocket_info");
+  return WebSocket.connect(
+    `ws://127.0.0.1:${info.port}/?token=${encodeURIComponent(info.token)}`
+  );
+};
+
+export const PROTOCOL_VERSION = 1;
+
+// 注册消息监听后再发送 hello，服务端回复 welcome 帧携带 session_id
+export const sendHello = (
+  ws: WebSocket,
+  channels: Array<"log" | "exec" | "watch">,
+  sessionId?: string
+) => {
+  return ws.send(
+    JSON.stringify({
+      type: "hello",
+      version: PROTOCOL_VERSION,
+      session_id: sessionId,
+      channels: channels,
+    })
+  );
+};
+
+// 连接断开时监听器收到 Close 帧，异常断开时收到错误字符串
+export const isCloseMessage = (msg: Message) =>
+  typeof msg === "string" || msg.type === "Close";
+
+const RESUME_ATTEMPTS = 5;
+const RESUME_TIMEOUT = 5000;
+
+// 连接意外断开后带上原 session_id 重连，服务端在宽限期内恢复会话并继续推送未发送的消息
+// 会话已过期（welcome 帧 resumed 为 false）或多次重连失败时返回 null
+export const resumeWebsocket = async (
+  sessionId: string,
+  channels: Array<"log" | "exec" | "watch">,
+  listener: (ws: WebSocket, msg: Message) => void
+): Promise<WebSocket | null> => {
+  for (let attempt = 1; attempt <= RESUME_ATTEMPTS; attempt++) {
+    await new Promise((resolve) => setTimeout(resolve, attempt * 1000));
+    let ws: WebSocket | null = null;
+    try {
+      const socket = await connectWebsocket();
+      ws = socket;
+      const resumed = await new Promise<boolean>((resolve, reject) => {
+        const timeout = setTimeout(() => {
+          reject(new Error("Resume timeout"));
+        }, RESUME_TIMEOUT);
+
+        socket.addListener((msg) => {
+          if (msg.type === "Text") {
+            const frame = JSON.parse(msg.data?.toString() || "{}");
+            if (frame.type === "welcome") {
+              clearTimeout(timeout);
+              resolve(frame.resumed && frame.session_id === sessionId);
+              return;
+            }
+          }
+          listener(socket, msg);
+        });
+        sendHello(socket, channels, sessionId);
+      });
+      if (resumed) {
+        return socket;
+      }
+      await socket.disconnect();
+      return null;
+    } catch (error) {
+      await ws?.disconnect().catch(() => {});
+    }
+  }
+  return null;
+};
+
+// 二进制帧格式：[channel 长度][channel][stream][数据]，stream 0/1/2 对应 stdin/stdout/stderr
+export const encodeBinaryFrame = (channel: string, data: Uint8Array) => {
+  const channelBytes = new TextEncoder().encode(channel);
+  const frame = new Uint8Array(channelBytes.length + data.length + 2);
+  frame[0] = channelBytes.length;
+  frame.set(channelBytes, 1);
+  frame[channelBytes.length + 1] = 0;
+  frame.set(data, channelBytes.length + 2);
+  return Array.from(frame);
+};
+
+export const decodeBinaryFrame = (frame: ArrayLike<number>) => {
+  const bytes = Uint8Array.from(frame);
+  const length = bytes[0];
+  return {
+    channel: new TextDecoder().decode(bytes.subarray(1, length + 1)),
+    stream: bytes[length + 1],
+    data: bytes.subarray(length + 2),
+  };
+};
@@ -2,7 +2,13 @@ import { useParams, useSearchParams } from "react-router-dom";
 import CustomEdit from "../CustomEdit";
 import { useState, useEffect, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import WebSocket from "@tauri-apps/plugin-websocket";
+import WebSocket, { Message } from "@tauri-apps/plugin-websocket";
+import {
+  connectWebsocket,
+  isCloseMessage,
+  resumeWebsocket,
+  sendHello,
+} from "@/api/websocket";
 import {
   Button,
   Checkbox,
@@ -67,6 +73,7 @@ const Log = ({
 
   const { formatMessage } = useLocale();
   const wsRef = useRef<WebSocket | null>(null);
+  const sessionRef = useRef<string>("");
   const pause = useRef<boolean>(false);
   const clean = useRef<boolean>(false);
 
@@ -100,10 +107,13 @@ const Log = ({
       });
   };
 
+  // 先置空引用，主动断开不会触发重连
   const cleanupWebSocket = () => {
-    if (wsRef.current) {
-      wsRef.current.disconnect();
-      wsRef.current = null;
+    const ws = wsRef.current;
+    wsRef.current = null;
+    sessionRef.current = "";
+    if (ws) {
+      ws.disconnect();
     }
     setIsConnected(false);
   };
@@ -130,8 +140,70 @@ const Log = ({
 
       let text = "";
       let clientId: string | undefined;
-      const ws = await WebSocket.connect("ws://localhost:38012");
+      const channelId = crypto.randomUUID();
+      const ws = await connectWebsocket();
       wsRef.current = ws;
+      const handleMessage = (ws: WebSocket, msg: Message) => {
+        if (isCloseMessage(msg)) {
+          if (wsRef.current === ws) {
+            resumeLogStream();
+          }
+          return;
+        }
+
+        if (msg.type === "Ping") {
+          ws.send({ type: "Pong", data: [1] });
+          return;
+        }
+
+        if (msg.type === "Text") {
+          const frame = JSON.parse(msg.data?.toString() || "{}");
+          if (frame.channel !== channelId || frame.type !== "data") {
+            return;
+          }
+          if (clean.current) {
+            text = "";
+            clean.current = false;
+          }
+          // 服务端会把多行日志合并成一帧，按行计数
+          lineNumbers.current += String(frame.data).split("\n").length;
+          if (lineNumbers.current > 3000) {
+            lineNumbers.current = 0;
+            text = "";
+          }
+          text = text + frame.data + "\n";
+          if (!pause.current) {
+            // setLogs(text);
+            logRef.current = text;
+          }
+        }
+      };
+
+      // 连接意外断开时带上 session_id 重连，日志流继续推送到原 channel
+      const resumeLogStream = async () => {
+        const sessionId = sessionRef.current;
+        wsRef.current = null;
+        setIsConnected(false);
+
+        const resumed = await resumeWebsocket(
+          sessionId,
+          ["log"],
+          handleMessage
+        );
+        // 重连期间用户主动断开或重新开始了日志流
+        if (sessionRef.current !== sessionId) {
+          resumed?.disconnect();
+          return;
+        }
+        if (!resumed) {
+          sessionRef.current = "";
+          messageApi.error(formatMessage({ id: "message.disconnected" }));
+          return;
+        }
+        wsRef.current = resumed;
+        setIsConnected(true);
+      };
+
       const waitForClientId = new Promise<string>((resolve, reject) => {
         const timeout = setTimeout(() => {
           setIsLoading(false);
@@ -139,35 +211,19 @@ const Log = ({
         }, 5000);
 
         ws.addListener((msg) => {
-          if (!clientId || clientId === "") {
-            clientId = msg.data?.toString() || "";
-            clearTimeout(timeout);
-            resolve(clientId);
-            return;
-          }
-
-          if (msg.type === "Ping") {
-            ws.send({ type: "Pong", data: [1] });
-            return;
-          }
-
           if (msg.type === "Text") {
-            if (clean.current) {
-              text = "";
-              clean.current = false;
-            }
-            lineNumbers.current++;
-            if (lineNumbers.current > 3000) {
-              lineNumbers.current = 0;
-              text = "";
-            }
-            text = text + msg.data?.toString() + "\n";
-            if (!pause.current) {
-              // setLogs(text);
-              logRef.current = text;
+            const frame = JSON.parse(msg.data?.toString() || "{}");
+            if (frame.type === "welcome") {
+              clientId = frame.session_id as string;
+              sessionRef.current = clientId;
+              clearTimeout(timeout);
+              resolve(clientId);
+              return;
             }
           }
+          handleMessage(ws, msg);
         });
+        sendHello(ws, ["log"]);
       });
 
       try {
@@ -184,6 +240,7 @@ const Log = ({
               pod: name,
             },
             clientId: clientId,
+            channelId: channelId,
           });
           setIsLoading(false);
           messageApi.success(
@@ -4,7 +4,15 @@ import { Terminal } from "@xterm/xterm";
 import { FitAddon } from "@xterm/addon-fit";
 import { WebLinksAddon } from "@xterm/addon-web-links";
 import { invoke } from "@tauri-apps/api/core";
-import WebSocket from "@tauri-apps/plugin-websocket";
+import WebSocket, { Message } from "@tauri-apps/plugin-websocket";
+import {
+  connectWebsocket,
+  decodeBinaryFrame,
+  encodeBinaryFrame,
+  isCloseMessage,
+  resumeWebsocket,
+  sendHello,
+} from "@/api/websocket";
 import { Button, message, Select, Typography, Space, Spin, theme } from "antd";
 import { AppsV1Url, kubeApi } from "@/api/cluster";
 import { Pod } from "kubernetes-models/v1";
@@ -35,6 +43,7 @@ const TerminalWindow = ({
   const fitAddonRef = useRef<FitAddon | null>(null);
   const wsRef = useRef<WebSocket | null>(null);
   const clientIdRef = useRef<string>("");
+  const channelIdRef = useRef<string>("");
 
   const [queryParams] = useSearchParams();
   const initContainerName = queryParams.get("container");
@@ -176,21 +185,108 @@ const TerminalWindow = ({
         return;
       }
       if (wsRef.current && clientIdRef.current) {
-        wsRef.current.send(data);
+        wsRef.current.send({
+          type: "Binary",
+          data: encodeBinaryFrame(
+            channelIdRef.current,
+            new TextEncoder().encode(data)
+          ),
+        });
+      }
+    });
+
+    // 终端大小变化时同步到远端 shell
+    term.onResize(({ cols, rows }) => {
+      if (wsRef.current && clientIdRef.current) {
+        wsRef.current.send(
+          JSON.stringify({
+            type: "resize",
+            channel: channelIdRef.current,
+            cols: cols,
+            rows: rows,
+          })
+        );
       }
     });
 
     window.addEventListener("resize", handleResize);
   };
 
-  // 清理 WebSocket 连接
+  // 清理 WebSocket 连接，先置空引用，主动断开不会触发重连
   const cleanupWebSocket = () => {
-    if (wsRef.current) {
-      wsRef.current.disconnect();
-      wsRef.current = null;
+    const ws = wsRef.current;
+    wsRef.current = null;
+    clientIdRef.current = "";
+    if (ws) {
+      ws.disconnect();
     }
     setIsConnected(false);
-    clientIdRef.current = "";
+  };
+
+  // 处理会话消息，重连后的新连接复用同一处理逻辑
+  const handleMessage = (ws: WebSocket, msg: Message) => {
+    if (isCloseMessage(msg)) {
+      if (wsRef.current === ws) {
+        resumeTerminal();
+      }
+      return;
+    }
+
+    if (msg.type === "Ping") {
+      ws.send({ type: "Pong", data: [1] });
+      return;
+    }
+
+    if (msg.type === "Text" && terminalInstanceRef.current) {
+      const frame = JSON.parse(msg.data?.toString() || "{}");
+      if (frame.channel !== channelIdRef.current) {
+        return;
+      }
+      if (frame.type === "status") {
+        return;
+      }
+      if (frame.type === "closed") {
+        setIsConnected(false);
+        terminalInstanceRef.current.write("\r\nTerminal disconnected.\r\n");
+        return;
+      }
+      const data: string = frame.data || "";
+      if (data.startsWith("OCI runtime exec")) {
+        setIsConnected(false);
+      }
+      terminalInstanceRef.current.write(data);
+    }
+
+    if (msg.type === "Binary" && terminalInstanceRef.current) {
+      const frame = decodeBinaryFrame(msg.data as number[]);
+      if (frame.channel === channelIdRef.current) {
+        terminalInstanceRef.current.write(frame.data);
+      }
+    }
+  };
+
+  // 连接意外断开时带上 session_id 重连，远端 shell 继续输出到原 channel
+  const resumeTerminal = async () => {
+    const sessionId = clientIdRef.current;
+    wsRef.current = null;
+    setIsConnected(false);
+    terminalInstanceRef.current?.write(
+      "\r\nConnection lost, reconnecting...\r\n"
+    );
+
+    const ws = await resumeWebsocket(sessionId, ["exec"], handleMessage);
+    // 重连期间用户主动断开或重新连接了终端
+    if (clientIdRef.current !== sessionId) {
+      ws?.disconnect();
+      return;
+    }
+    if (!ws) {
+      clientIdRef.current = "";
+      terminalInstanceRef.current?.write("\r\nTerminal disconnected.\r\n");
+      return;
+    }
+    wsRef.current = ws;
+    setIsConnected(true);
   };
 
   // 连接终端
@@ -211,7 +307,7 @@ const TerminalWindow = ({
       }
 
       // 建立 WebSocket 连接
-      const ws = await WebSocket.connect("ws://localhost:38012");
+      const ws = await connectWebsocket();
       wsRef.current = ws;
 
       const waitForClientId = new Promise<string>((resolve, reject) => {
@@ -221,40 +317,23 @@ const TerminalWindow = ({
         }, 10000);
 
         ws.addListener((msg) => {
-          if (!clientIdRef.current || clientIdRef.current === "") {
-            const receivedId = msg.data?.toString() || "";
-            if (receivedId && receivedId.length > 10) {
-              clientIdRef.current = receivedId;
+          if (msg.type === "Text") {
+            const frame = JSON.parse(msg.data?.toString() || "{}");
+            if (frame.type === "welcome") {
+              clientIdRef.current = frame.session_id;
               clearTimeout(timeout);
-              resolve(receivedId);
+              resolve(frame.session_id);
               return;
             }
-          }
-
-          if (msg.type === "Ping") {
-            ws.send({ type: "Pong", data: [1] });
-            return;
-          }
-
-          if (msg.type === "Text" && terminalInstanceRef.current) {
-            const data = msg.data?.toString() || "";
-            if (data.startsWith("OCI runtime exec")) {
-              terminalInstanceRef.current.write(data);
-              reject(new Error("Connection failed"));
-              setIsConnected(false);
+            if (frame.type === "error") {
+              clearTimeout(timeout);
+              reject(new Error(frame.message));
               return;
             }
-            terminalInstanceRef.current.write(data);
-          }
-
-          if (msg.type === "Binary" && terminalInstanceRef.current) {
-            const data = new TextDecoder().decode(
-              msg.data as unknown as ArrayBuffer
-            );
-
-            terminalInstanceRef.current.write(data);
           }
+          handleMessage(ws, msg);
         });
+        sendHello(ws, ["exec"]);
       });
 
       try {
@@ -267,14 +346,18 @@ const TerminalWindow = ({
           }
 
           try {
+            channelIdRef.current = crypto.randomUUID();
             invoke("pod_terminal", {
               podTerminal: {
                 namespace: finalNamespace,
                 name: finalPodName,
                 container: selectedContainer,
-                command: ["/bin/sh"], // 使用 sh 作为默认shell
+                cols: terminalInstanceRef.current?.cols,
+                rows: terminalInstanceRef.current?.rows,
+                binary: true,
               },
               clientId: receivedClientId,
+              channelId: channelIdRef.current,
             });
 
             // 连接成功后重新调整终端大小